@@ -0,0 +1,100 @@
+use ::core::ffi::CStr;
+use ::std::ffi::CString;
+
+use crate::{
+	snapshot::OwnedObject,
+	Segment,
+};
+
+/// Information about the loaded object that contains some address,
+/// as returned by [`Objects::object_containing`](crate::Objects::object_containing).
+#[derive(Debug, Clone)]
+pub struct AddressInfo {
+	object: OwnedObject,
+	segment: usize,
+	addr: usize,
+	symbol: Option<NearestSymbol>,
+}
+
+impl AddressInfo {
+	pub(crate) const fn new(object: OwnedObject, segment: usize, addr: usize, symbol: Option<NearestSymbol>) -> Self {
+		Self {
+			object,
+			segment,
+			addr,
+			symbol,
+		}
+	}
+
+	/// Returns the object that contains the address.
+	pub const fn object(&self) -> &OwnedObject {
+		&self.object
+	}
+
+	/// Returns the segment of the object that contains the address.
+	pub fn segment(&self) -> Segment<'_> {
+		match self.object.segments().nth(self.segment) {
+			Some(segment) => segment,
+			None => unreachable!("segment index should be in bounds of the copied object"),
+		}
+	}
+
+	/// Returns the index of the [`segment`](Self::segment) in the object's segments.
+	pub const fn segment_index(&self) -> usize {
+		self.segment
+	}
+
+	/// Returns the address that was looked up.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	/// Returns the offset of the address from the object's base address.
+	pub fn offset(&self) -> usize {
+		self.addr.wrapping_sub(self.object.base_addr())
+	}
+
+	/// Returns the nearest exported symbol at or below the address, if there is one.
+	pub const fn symbol(&self) -> Option<&NearestSymbol> {
+		self.symbol.as_ref()
+	}
+}
+
+/// Exported symbol that is closest to a looked up address.
+#[derive(Debug, Clone)]
+pub struct NearestSymbol {
+	name: CString,
+	addr: usize,
+	offset: usize,
+}
+
+impl NearestSymbol {
+	pub(crate) const fn new(name: CString, addr: usize, offset: usize) -> Self {
+		Self {
+			name,
+			addr,
+			offset,
+		}
+	}
+
+	pub fn name(&self) -> &CStr {
+		&self.name
+	}
+
+	/// Returns the demangled name of the symbol,
+	/// or the name itself if it isn't mangled.
+	#[cfg(feature = "demangle")]
+	pub fn demangled_name(&self) -> ::std::borrow::Cow<'_, str> {
+		crate::demangle::demangle_lossy(self.name.to_bytes())
+	}
+
+	/// Returns the address of the symbol.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	/// Returns the offset of the looked up address from the address of the symbol.
+	pub const fn offset(&self) -> usize {
+		self.offset
+	}
+}
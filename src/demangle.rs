@@ -0,0 +1,39 @@
+use ::std::borrow::Cow;
+
+/// Demangles a Rust (legacy or v0) or Itanium C++ symbol name,
+/// returning `None` if it isn't mangled in either scheme.
+/// 
+/// Rust hashes such as `::h0123456789abcdef` are left out.
+pub fn demangle(name: &str) -> Option<String> {
+	if let Ok(demangled) = ::rustc_demangle::try_demangle(name) {
+		return Some(format!("{demangled:#}"))
+	}
+	demangle_cpp(name.as_bytes())
+}
+
+/// Demangles an Itanium C++ symbol name or type name.
+fn demangle_cpp(name: &[u8]) -> Option<String> {
+	let symbol = ::cpp_demangle::Symbol::new(name).ok()?;
+	symbol.demangle(&::cpp_demangle::DemangleOptions::default()).ok()
+}
+
+/// Demangles `name` like [`demangle`],
+/// falling back to the name itself if it isn't mangled or isn't valid UTF-8.
+pub fn demangle_lossy(name: &[u8]) -> Cow<'_, str> {
+	match ::core::str::from_utf8(name) {
+		Ok(name) => match demangle(name) {
+			Some(demangled) => Cow::Owned(demangled),
+			None => Cow::Borrowed(name),
+		},
+		Err(..) => String::from_utf8_lossy(name),
+	}
+}
+
+/// Demangles the `typeinfo name` string of a C++ type, such as `N3foo3BarIiEE`.
+pub fn demangle_type_name(mangled: &[u8]) -> Option<String> {
+	let mut symbol = Vec::with_capacity(mangled.len() + 4);
+	symbol.extend_from_slice(b"_ZTS");
+	symbol.extend_from_slice(mangled);
+	let demangled = demangle_cpp(&symbol)?;
+	demangled.strip_prefix("typeinfo name for ").map(str::to_owned)
+}
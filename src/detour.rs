@@ -0,0 +1,473 @@
+use ::core::{
+	fmt,
+	ops::Range,
+	ptr::copy_nonoverlapping,
+	sync::atomic::{
+		AtomicBool, AtomicU64, AtomicUsize, Ordering,
+	},
+};
+use ::libc::{
+	c_int,
+	PROT_EXEC, PROT_READ, PROT_WRITE,
+};
+
+use crate::{
+	near::{
+		Block, MemoryKind, NearAllocator,
+	},
+	os::unix::{
+		with_protection, Error as UnixError,
+	},
+	scan::is_code_segment,
+	x86_64::{
+		Instruction, OpcodeMap,
+	},
+	Error, Object, Symbols,
+};
+
+/// Length of a `jmp rel32`, which is what is written over the start of a detoured function.
+pub const JMP_REL32_LEN: usize = 5;
+
+/// Largest number of bytes that can be overwritten at the target,
+/// which is the `jmp rel32` extended to the end of the last instruction that it overlaps.
+const MAX_PATCH_LEN: usize = JMP_REL32_LEN - 1 + crate::x86_64::MAX_INSTRUCTION_LEN;
+
+/// Largest size of the trampoline and the relay of a detour.
+const STUB_SIZE: usize = 128;
+
+/// Function pointer type that can be detoured, such as `unsafe extern "C" fn(i32) -> i32`.
+/// 
+/// This is implemented for the Rust, `C` and `system` ABIs with up to 12 arguments.
+/// 
+/// # Safety
+/// The type must be a function pointer with the same representation as `usize`.
+pub unsafe trait FnPtr: Copy {
+	/// Arguments of the function, as a tuple.
+	type Args;
+	/// Return type of the function.
+	type Output;
+
+	fn addr(self) -> usize;
+
+	/// Creates a function pointer to `addr`.
+	/// 
+	/// # Safety
+	/// `addr` must be the address of a function with this signature.
+	unsafe fn from_addr(addr: usize) -> Self;
+
+	/// Calls the function with the tuple of `args`.
+	/// 
+	/// # Safety
+	/// The function must be safe to call with `args`.
+	unsafe fn call(self, args: Self::Args) -> Self::Output;
+}
+
+macro_rules! fn_ptr_impls {
+	($($arg:ident)*) => {
+		fn_ptr_impls!(@impl [$($arg)*] fn($($arg),*) -> R);
+		fn_ptr_impls!(@impl [$($arg)*] unsafe fn($($arg),*) -> R);
+		fn_ptr_impls!(@impl [$($arg)*] extern "C" fn($($arg),*) -> R);
+		fn_ptr_impls!(@impl [$($arg)*] unsafe extern "C" fn($($arg),*) -> R);
+		fn_ptr_impls!(@impl [$($arg)*] extern "system" fn($($arg),*) -> R);
+		fn_ptr_impls!(@impl [$($arg)*] unsafe extern "system" fn($($arg),*) -> R);
+	};
+	(@impl [$($arg:ident)*] $ty:ty) => {
+		unsafe impl<R, $($arg),*> FnPtr for $ty {
+			type Args = ($($arg,)*);
+			type Output = R;
+
+			fn addr(self) -> usize {
+				self as usize
+			}
+
+			unsafe fn from_addr(addr: usize) -> Self {
+				unsafe { ::core::mem::transmute::<usize, Self>(addr) }
+			}
+
+			#[allow(non_snake_case, unused_unsafe)]
+			unsafe fn call(self, ($($arg,)*): Self::Args) -> R {
+				unsafe { (self)($($arg),*) }
+			}
+		}
+	};
+}
+
+fn_ptr_impls!();
+fn_ptr_impls!(A);
+fn_ptr_impls!(A B);
+fn_ptr_impls!(A B C);
+fn_ptr_impls!(A B C D);
+fn_ptr_impls!(A B C D E);
+fn_ptr_impls!(A B C D E F);
+fn_ptr_impls!(A B C D E F G);
+fn_ptr_impls!(A B C D E F G H);
+fn_ptr_impls!(A B C D E F G H I);
+fn_ptr_impls!(A B C D E F G H I J);
+fn_ptr_impls!(A B C D E F G H I J K);
+fn_ptr_impls!(A B C D E F G H I J K L);
+
+/// Returns the `rel32` displacement from `next` to `target`, if it fits.
+fn rel32(next: usize, target: usize) -> Option<i32> {
+	i32::try_from(target.wrapping_sub(next) as isize).ok()
+}
+
+fn unsupported(what: &'static str) -> Error {
+	Error(UnixError::Unsupported(what))
+}
+
+/// Appends `instruction` to `code`, which will be located at `addr`,
+/// adjusting its RIP-relative operand or branch offset to keep the same target.
+/// 
+/// Short branches are widened to `rel32`.
+/// Branches back into `overwritten` can't be relocated, since those bytes are replaced by the patch.
+fn relocate(instruction: &Instruction, addr: usize, overwritten: &Range<usize>, code: &mut Vec<u8>) -> Result<(), Error> {
+	if let Some(target) = instruction.branch_target() {
+		if overwritten.contains(&target) {
+			return Err(unsupported("branch into the overwritten instructions"))
+		}
+		let (opcode, len): (&[u8], usize) = match (instruction.map(), instruction.opcode()) {
+			(OpcodeMap::Primary, 0xe8) => (&[0xe8], 5),
+			(OpcodeMap::Primary, 0xe9 | 0xeb) => (&[0xe9], 5),
+			(OpcodeMap::Primary, cc @ 0x70..=0x7f) => (&[0x0f, 0x80 | (cc & 0x0f)], 6),
+			(OpcodeMap::Secondary, cc @ 0x80..=0x8f) => (&[0x0f, cc], 6),
+			_ => return Err(unsupported("loop or jrcxz instruction")),
+		};
+		let disp = rel32(addr + len, target).ok_or(unsupported("branch target is out of reach"))?;
+		code.extend_from_slice(opcode);
+		code.extend_from_slice(&disp.to_le_bytes());
+	} else if let (Some(target), Some(offset)) = (instruction.rip_target(), instruction.displacement_offset()) {
+		let disp = rel32(addr + instruction.len(), target).ok_or(unsupported("RIP-relative operand is out of reach"))?;
+		let start = code.len();
+		code.extend_from_slice(instruction.bytes());
+		code[start + offset..start + offset + 4].copy_from_slice(&disp.to_le_bytes());
+	} else {
+		code.extend_from_slice(instruction.bytes());
+	}
+	Ok(())
+}
+
+/// Returns `true` if execution never continues after `instruction`,
+/// such as after a `ret` or an unconditional `jmp`.
+fn ends_flow(instruction: &Instruction) -> bool {
+	instruction.map() == OpcodeMap::Primary && match instruction.opcode() {
+		0xc2 | 0xc3 | 0xca | 0xcb | 0xcc | 0xe9 | 0xea | 0xeb => true,
+		// `jmp r/m`
+		0xff => instruction.modrm_reg().is_some_and(move |reg| reg & 7 == 4 || reg & 7 == 5),
+		_ => false,
+	}
+}
+
+/// Writes `bytes` over the code at `addr`, making its pages writable for the duration of the write
+/// and then restoring them to `prot`.
+/// 
+/// Bytes that fit in an aligned 8-byte word are written at once,
+/// so that other threads never run a partially written patch.
+/// 
+/// # Safety
+/// `addr` must be in a readable and executable segment that is mapped with `prot`,
+/// and nothing may be running the overwritten bytes.
+unsafe fn write_code(addr: usize, bytes: &[u8], prot: c_int) -> Result<(), Error> {
+	let range = addr..addr + bytes.len();
+	let write = move || unsafe {
+		let word_addr = addr & !7;
+		if range.end <= word_addr + 8 {
+			let word = &*(word_addr as *const AtomicU64);
+			let mut value = word.load(Ordering::SeqCst).to_le_bytes();
+			value[addr - word_addr..][..bytes.len()].copy_from_slice(bytes);
+			word.store(u64::from_le_bytes(value), Ordering::SeqCst);
+		} else {
+			copy_nonoverlapping(bytes.as_ptr(), addr as *mut u8, bytes.len());
+		}
+	};
+	unsafe { with_protection(addr..addr + bytes.len(), PROT_READ | PROT_WRITE | PROT_EXEC, prot, write) }
+		.map_err(Error)
+}
+
+/// Untyped inline hook, which is shared by [`Detour`] and the hook chains in [`hook`](crate::hook).
+/// 
+/// The target jumps to a relay next to the trampoline,
+/// which jumps to the address in a separate writable cell,
+/// so that the destination can be changed without writing to code.
+/// 
+/// The object of the target is kept loaded, so that the original bytes can always be restored.
+#[derive(Debug)]
+pub(crate) struct RawDetour {
+	target: usize,
+	/// Protection of the segment of the target.
+	prot: c_int,
+	trampoline: usize,
+	relay: usize,
+	destination: &'static AtomicUsize,
+	original: [u8; MAX_PATCH_LEN],
+	patch: [u8; MAX_PATCH_LEN],
+	patch_len: usize,
+	enabled: AtomicBool,
+	_symbols: Option<Symbols>,
+}
+
+// SAFETY: The handle in `Symbols` is only closed.
+unsafe impl Send for RawDetour {}
+unsafe impl Sync for RawDetour {}
+
+impl RawDetour {
+	pub(crate) unsafe fn new(object: &Object<'_>, target: usize, destination: usize) -> Result<Self, Error> {
+		let segment = object.segments()
+			.find(move |segment| is_code_segment(segment) && segment.contains(target))
+			.ok_or(Error(UnixError::NotFound))?;
+
+		let mut instructions = Vec::new();
+		let mut patch_len = 0;
+		for instruction in object.instructions_from(target) {
+			patch_len += instruction.len();
+			let ends_flow = ends_flow(&instruction);
+			instructions.push(instruction);
+			if patch_len >= JMP_REL32_LEN {
+				break
+			}
+			if ends_flow {
+				return Err(unsupported("function is too short to be detoured"))
+			}
+		}
+		if patch_len < JMP_REL32_LEN || target + patch_len > segment.addr_range().end {
+			return Err(unsupported("instructions at the target can't be decoded"))
+		}
+
+		// The destination is kept in a separate block of data, which stays writable.
+		let allocator = NearAllocator::global();
+		let code_block = allocator.alloc(object.as_unix_object().load_range(), STUB_SIZE, MemoryKind::Code)?;
+		let cell_block = match allocator.alloc(code_block.range(), size_of::<usize>(), MemoryKind::Data) {
+			Ok(block) => block,
+			Err(error) => {
+				unsafe { let _ = allocator.free(code_block); }
+				return Err(error)
+			}
+		};
+		let prot = segment.flags().as_unix_flags().prot();
+		let result = unsafe { Self::build(target, prot, &instructions, patch_len, &code_block, &cell_block, destination) };
+		match result {
+			Ok(raw) => Ok(Self {
+				_symbols: object.symbols().ok(),
+				..raw
+			}),
+			Err(error) => {
+				unsafe {
+					let _ = allocator.free(code_block);
+					let _ = allocator.free(cell_block);
+				}
+				Err(error)
+			}
+		}
+	}
+
+	/// Writes the trampoline and the relay into `code_block` and the destination into `cell_block`.
+	/// 
+	/// The object of the target is only kept loaded by [`new`](Self::new).
+	unsafe fn build(
+		target: usize,
+		prot: c_int,
+		instructions: &[Instruction],
+		patch_len: usize,
+		code_block: &Block,
+		cell_block: &Block,
+		destination: usize,
+	) -> Result<Self, Error> {
+		let stub = code_block.addr();
+		let cell = cell_block.addr();
+		let overwritten = target..target + patch_len;
+		let mut code = Vec::with_capacity(STUB_SIZE);
+		for instruction in instructions {
+			relocate(instruction, stub + code.len(), &overwritten, &mut code)?;
+		}
+		let disp = rel32(stub + code.len() + JMP_REL32_LEN, overwritten.end).ok_or(unsupported("target is out of reach"))?;
+		code.push(0xe9);
+		code.extend_from_slice(&disp.to_le_bytes());
+
+		let relay = stub + code.len().next_multiple_of(16);
+		code.resize(relay - stub, 0xcc);
+		// `jmp [rip+disp32]` to the destination in the cell.
+		let disp = rel32(relay + 6, cell).ok_or(unsupported("destination is out of reach"))?;
+		code.extend_from_slice(&[0xff, 0x25]);
+		code.extend_from_slice(&disp.to_le_bytes());
+		debug_assert!(code.len() <= STUB_SIZE);
+
+		let mut patch = [0xcc; MAX_PATCH_LEN];
+		patch[0] = 0xe9;
+		let disp = rel32(target + JMP_REL32_LEN, relay).ok_or(unsupported("relay is out of reach"))?;
+		patch[1..JMP_REL32_LEN].copy_from_slice(&disp.to_le_bytes());
+		let mut original = [0; MAX_PATCH_LEN];
+		let cell = unsafe {
+			copy_nonoverlapping(target as *const u8, original.as_mut_ptr(), patch_len);
+			cell_block.write(0, &destination.to_ne_bytes())?;
+			code_block.write(0, &code)?;
+			&*(cell as *const AtomicUsize)
+		};
+		Ok(Self {
+			target,
+			prot,
+			trampoline: stub,
+			relay,
+			destination: cell,
+			original,
+			patch,
+			patch_len,
+			enabled: AtomicBool::new(false),
+			_symbols: None,
+		})
+	}
+
+	pub(crate) const fn target(&self) -> usize {
+		self.target
+	}
+
+	pub(crate) const fn trampoline(&self) -> usize {
+		self.trampoline
+	}
+
+	pub(crate) const fn relay(&self) -> usize {
+		self.relay
+	}
+
+	pub(crate) fn original_bytes(&self) -> &[u8] {
+		&self.original[..self.patch_len]
+	}
+
+	/// Returns the address that the relay currently jumps to.
+	pub(crate) fn destination(&self) -> usize {
+		self.destination.load(Ordering::SeqCst)
+	}
+
+	/// Changes the address that the relay jumps to, which takes effect immediately.
+	pub(crate) fn set_destination(&self, destination: usize) {
+		self.destination.store(destination, Ordering::SeqCst);
+	}
+
+	pub(crate) fn is_enabled(&self) -> bool {
+		self.enabled.load(Ordering::SeqCst)
+	}
+
+	pub(crate) unsafe fn enable(&self) -> Result<(), Error> {
+		if self.enabled.swap(true, Ordering::SeqCst) {
+			return Ok(())
+		}
+		let result = unsafe { write_code(self.target, &self.patch[..self.patch_len], self.prot) };
+		if result.is_err() {
+			self.enabled.store(false, Ordering::SeqCst);
+		}
+		result
+	}
+
+	pub(crate) unsafe fn disable(&self) -> Result<(), Error> {
+		if !self.enabled.swap(false, Ordering::SeqCst) {
+			return Ok(())
+		}
+		let result = unsafe { write_code(self.target, &self.original[..self.patch_len], self.prot) };
+		if result.is_err() {
+			self.enabled.store(true, Ordering::SeqCst);
+		}
+		result
+	}
+}
+
+/// Inline hook that redirects a function to a detour by overwriting its first instructions with a `jmp`.
+/// 
+/// The overwritten instructions are relocated into a trampoline near the target,
+/// through which the original function can still be called.
+/// Since the detour may be too far away for a `jmp rel32`,
+/// the target actually jumps to a relay next to the trampoline, which jumps to the detour.
+/// 
+/// The detour starts out disabled.
+/// Dropping it disables it, but the trampoline stays allocated,
+/// since other threads may still be running it.
+/// The object of the target is kept loaded until the detour is dropped.
+/// 
+/// See [`Object::detour`].
+pub struct Detour<F: FnPtr> {
+	raw: RawDetour,
+	detour: F,
+}
+
+impl<F: FnPtr> Detour<F> {
+	pub(crate) unsafe fn new(object: &Object<'_>, target: usize, detour: F) -> Result<Self, Error> {
+		Ok(Self {
+			raw: unsafe { RawDetour::new(object, target, detour.addr())? },
+			detour,
+		})
+	}
+
+	/// Returns the address of the detoured function.
+	pub const fn target(&self) -> usize {
+		self.raw.target()
+	}
+
+	pub const fn detour(&self) -> F {
+		self.detour
+	}
+
+	/// Returns a pointer to the trampoline, which behaves like the original function.
+	pub fn original(&self) -> F {
+		// SAFETY: The trampoline runs the original function, so it has the same signature.
+		unsafe { F::from_addr(self.raw.trampoline()) }
+	}
+
+	/// Returns the address of the trampoline.
+	pub const fn trampoline_addr(&self) -> usize {
+		self.raw.trampoline()
+	}
+
+	/// Returns the address of the relay that the target jumps to, which jumps to the detour.
+	pub const fn relay_addr(&self) -> usize {
+		self.raw.relay()
+	}
+
+	/// Returns the bytes at the target that are overwritten while the detour is enabled.
+	pub fn original_bytes(&self) -> &[u8] {
+		self.raw.original_bytes()
+	}
+
+	/// Calls the original function through the trampoline.
+	/// 
+	/// # Safety
+	/// The original function must be safe to call with `args`.
+	pub unsafe fn call_original(&self, args: F::Args) -> F::Output {
+		unsafe { self.original().call(args) }
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.raw.is_enabled()
+	}
+
+	/// Redirects the target to the detour.
+	/// 
+	/// # Safety
+	/// No other thread may be running the first instructions of the target,
+	/// unless the patch fits in an aligned 8-byte word, in which case it is written at once.
+	/// Nothing else may have patched the target since the detour was created.
+	pub unsafe fn enable(&self) -> Result<(), Error> {
+		unsafe { self.raw.enable() }
+	}
+
+	/// Restores the original instructions of the target.
+	/// 
+	/// # Safety
+	/// See [`enable`](Self::enable).
+	pub unsafe fn disable(&self) -> Result<(), Error> {
+		unsafe { self.raw.disable() }
+	}
+}
+
+impl<F: FnPtr> Drop for Detour<F> {
+	fn drop(&mut self) {
+		let _ = unsafe { self.disable() };
+	}
+}
+
+impl<F: FnPtr> fmt::Debug for Detour<F> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Detour")
+			.field("target", &format_args!("{:#x}", self.target()))
+			.field("detour", &format_args!("{:#x}", self.detour.addr()))
+			.field("trampoline", &format_args!("{:#x}", self.trampoline_addr()))
+			.field("enabled", &self.is_enabled())
+			.finish()
+	}
+}
@@ -0,0 +1,319 @@
+use ::core::{
+	fmt,
+	sync::atomic::{
+		AtomicBool, AtomicUsize, Ordering,
+	},
+};
+use ::std::{
+	collections::{
+		btree_map::Entry as MapEntry,
+		BTreeMap,
+	},
+	sync::{
+		Arc, Mutex, MutexGuard,
+	},
+};
+
+use crate::{
+	detour::{
+		FnPtr, RawDetour,
+	},
+	os::{
+		unix::Error as UnixError,
+		*,
+	},
+	snapshot::OwnedObject,
+	watch::is_same_object,
+	Error, Object, Objects, Symbols,
+};
+
+/// Hook chains of all targets, by the address of their target.
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry::new());
+
+fn registry() -> MutexGuard<'static, Registry> {
+	REGISTRY.lock().unwrap_or_else(move |poisoned| poisoned.into_inner())
+}
+
+/// State of a hook that is shared between the registry and its [`ChainedHook`].
+#[derive(Debug)]
+struct Link {
+	/// Address of the function that the hook should call next.
+	next: AtomicUsize,
+	linked: AtomicBool,
+}
+
+#[derive(Debug)]
+struct Hook {
+	id: u64,
+	priority: i32,
+	addr: usize,
+	/// Object that contains the code of the hook,
+	/// or `None` if it isn't in any loaded object, such as for generated code.
+	object: Option<OwnedObject>,
+	/// Handle that keeps `object` loaded while the hook is in its chain,
+	/// or `None` if it can't be opened, such as for the vDSO, which can't be unloaded either.
+	_symbols: Option<Symbols>,
+	link: Arc<Link>,
+}
+
+// SAFETY: The handle in `Symbols` is only closed.
+unsafe impl Send for Hook {}
+
+impl Hook {
+	fn unlink(&self) {
+		self.link.linked.store(false, Ordering::SeqCst);
+	}
+}
+
+/// Detour of a target, along with its hooks in the order that they are called.
+#[derive(Debug)]
+struct Chain {
+	/// Detour of the target, which also keeps `object` loaded,
+	/// so that the target can still be restored when the last hook is removed.
+	detour: RawDetour,
+	object: OwnedObject,
+	hooks: Vec<Hook>,
+}
+
+impl Chain {
+	/// Points every hook to the one after it, and the last one to the original function.
+	/// 
+	/// This starts at the end of the chain,
+	/// so that a hook is only reachable once everything after it is linked.
+	fn relink(&self) {
+		let mut next = self.detour.trampoline();
+		for hook in self.hooks.iter().rev() {
+			hook.link.next.store(next, Ordering::SeqCst);
+			next = hook.addr;
+		}
+		self.detour.set_destination(next);
+	}
+
+	/// Relinks the chain after hooks were removed from it,
+	/// returning `false` if it is now empty and its target was restored.
+	fn relink_after_removal(&self) -> Result<bool, Error> {
+		self.relink();
+		if !self.hooks.is_empty() {
+			return Ok(true)
+		}
+		// The relay already jumps to the trampoline, so the chain keeps working if this fails.
+		unsafe { self.detour.disable()? };
+		Ok(false)
+	}
+}
+
+#[derive(Debug)]
+struct Registry {
+	chains: BTreeMap<usize, Chain>,
+	next_id: u64,
+	generation: Option<(u64, u64)>,
+}
+
+impl Registry {
+	const fn new() -> Self {
+		Self {
+			chains: BTreeMap::new(),
+			next_id: 0,
+			generation: None,
+		}
+	}
+
+	/// Unlinks the hooks whose code was unloaded,
+	/// and forgets the chains whose target was unloaded, returning the hooks that were removed.
+	/// 
+	/// Removed hooks should be dropped after the registry is unlocked,
+	/// since closing their handles may run the destructors of their objects, which may remove other hooks.
+	fn prune(&mut self, objects: &Objects) -> Result<Vec<Hook>, Error> {
+		let generation = ObjectsImpl::generation(&objects.0);
+		if generation.is_some() && generation == self.generation {
+			return Ok(Vec::new())
+		}
+		let snapshot = objects.snapshot()?;
+		self.generation = generation;
+		let is_loaded = |object: &OwnedObject| snapshot.iter().any(move |loaded| is_same_object(loaded, object));
+
+		let mut pruned = Vec::new();
+		self.chains.retain(|_, chain| {
+			if !is_loaded(&chain.object) {
+				// The code of the target is gone, so there is nothing to restore.
+				chain.hooks.iter().for_each(Hook::unlink);
+				pruned.append(&mut chain.hooks);
+				return false
+			}
+			let len = pruned.len();
+			pruned.extend(chain.hooks.extract_if(.., |hook| !hook.object.as_ref().is_none_or(is_loaded)));
+			pruned[len..].iter().for_each(Hook::unlink);
+			pruned.len() == len || chain.relink_after_removal().unwrap_or(true)
+		});
+		Ok(pruned)
+	}
+
+	/// Removes the hook with `id` from the chain of `target`, returning it.
+	/// 
+	/// See [`prune`](Self::prune) for when the hook should be dropped.
+	fn remove(&mut self, target: usize, id: u64) -> Result<Option<Hook>, Error> {
+		let Some(chain) = self.chains.get_mut(&target) else {
+			return Ok(None)
+		};
+		let Some(index) = chain.hooks.iter().position(move |hook| hook.id == id) else {
+			return Ok(None)
+		};
+		let hook = chain.hooks.remove(index);
+		hook.unlink();
+		if !chain.relink_after_removal()? {
+			self.chains.remove(&target);
+		}
+		Ok(Some(hook))
+	}
+}
+
+/// Adds `hook` to the chain of hooks of the function at `target` in `object`,
+/// creating the chain if it doesn't exist yet.
+pub(crate) unsafe fn hook<F: FnPtr>(object: &Object<'_>, target: usize, hook: F, priority: i32) -> Result<ChainedHook<F>, Error> {
+	let objects = Objects::new();
+	let hook_object = objects.object_containing(hook.addr())?.map(move |info| info.object().clone());
+	let symbols = hook_object.as_ref().and_then(move |object| object.object().symbols().ok());
+	// Declared before the guard, so that removed hooks are dropped after the registry is unlocked.
+	let mut removed;
+	let mut registry = registry();
+	removed = registry.prune(&objects)?;
+	let id = registry.next_id;
+	registry.next_id += 1;
+
+	let chain = match registry.chains.entry(target) {
+		MapEntry::Occupied(entry) => entry.into_mut(),
+		MapEntry::Vacant(entry) => {
+			let info = objects.object_containing(target)?.ok_or(Error(UnixError::NotFound))?;
+			entry.insert(Chain {
+				detour: unsafe { RawDetour::new(object, target, hook.addr())? },
+				object: info.object().clone(),
+				hooks: Vec::new(),
+			})
+		}
+	};
+	let link = Arc::new(Link {
+		next: AtomicUsize::new(chain.detour.trampoline()),
+		linked: AtomicBool::new(true),
+	});
+	// Hooks with the same priority are called in the order that they were added.
+	let index = chain.hooks.iter().position(move |hook| hook.priority < priority).unwrap_or(chain.hooks.len());
+	chain.hooks.insert(index, Hook {
+		id,
+		priority,
+		addr: hook.addr(),
+		object: hook_object,
+		_symbols: symbols,
+		link: link.clone(),
+	});
+	chain.relink();
+	if let Err(error) = unsafe { chain.detour.enable() } {
+		removed.extend(registry.remove(target, id)?);
+		return Err(error)
+	}
+	Ok(ChainedHook {
+		target,
+		id,
+		priority,
+		hook,
+		link,
+		removed: false,
+	})
+}
+
+/// Forgets the chains whose target was in an object that has since been unloaded,
+/// and unlinks the hooks whose code was unloaded, returning how many hooks were unlinked.
+/// 
+/// Chains keep the object of their target loaded, and hooks the object that contains their code,
+/// so this only affects objects that can't be opened.
+/// This is also done whenever a hook is added,
+/// and can be called from [`ObjectWatcher::on_removed`](crate::watch::ObjectWatcher::on_removed).
+pub fn prune() -> Result<usize, Error> {
+	let pruned = registry().prune(&Objects::new())?;
+	Ok(pruned.len())
+}
+
+/// Hook in the chain of hooks of a target function, which removes itself when dropped.
+/// 
+/// Every target has a single [detour](crate::detour), which calls the hook with the highest priority.
+/// Each hook calls the [`next`](Self::next) function in the chain,
+/// which is either the hook with the next lower priority or the original function,
+/// so hooks can be added and removed in any order without disturbing the others.
+/// 
+/// The object that contains the code of the hook is kept loaded until the hook is removed.
+/// 
+/// See [`Object::hook_chained`].
+pub struct ChainedHook<F: FnPtr> {
+	target: usize,
+	id: u64,
+	priority: i32,
+	hook: F,
+	link: Arc<Link>,
+	/// Set by [`remove`](Self::remove), so that the hook isn't removed again when it's dropped.
+	removed: bool,
+}
+
+impl<F: FnPtr> ChainedHook<F> {
+	/// Returns the address of the hooked function.
+	pub const fn target(&self) -> usize {
+		self.target
+	}
+
+	pub const fn priority(&self) -> i32 {
+		self.priority
+	}
+
+	pub const fn hook(&self) -> F {
+		self.hook
+	}
+
+	/// Returns the function that the hook should call to continue the chain.
+	pub fn next(&self) -> F {
+		// SAFETY: All of the hooks of a target and its trampoline have the signature of the target.
+		unsafe { F::from_addr(self.link.next.load(Ordering::SeqCst)) }
+	}
+
+	/// Calls the [`next`](Self::next) function in the chain.
+	/// 
+	/// # Safety
+	/// The function must be safe to call with `args`.
+	pub unsafe fn call_next(&self, args: F::Args) -> F::Output {
+		unsafe { self.next().call(args) }
+	}
+
+	/// Returns `true` if the hook is still in its chain,
+	/// which is no longer the case once the object that contains its code was unloaded.
+	pub fn is_linked(&self) -> bool {
+		self.link.linked.load(Ordering::SeqCst)
+	}
+
+	/// Removes the hook from its chain,
+	/// restoring the target if it was the last one.
+	pub fn remove(mut self) -> Result<(), Error> {
+		self.removed = true;
+		// The registry is unlocked at the end of this statement, before the hook is dropped.
+		let removed = registry().remove(self.target, self.id)?;
+		drop(removed);
+		Ok(())
+	}
+}
+
+impl<F: FnPtr> Drop for ChainedHook<F> {
+	fn drop(&mut self) {
+		if self.removed {
+			return
+		}
+		let removed = registry().remove(self.target, self.id);
+		drop(removed);
+	}
+}
+
+impl<F: FnPtr> fmt::Debug for ChainedHook<F> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ChainedHook")
+			.field("target", &format_args!("{:#x}", self.target))
+			.field("priority", &self.priority)
+			.field("hook", &format_args!("{:#x}", self.hook.addr()))
+			.field("linked", &self.is_linked())
+			.finish()
+	}
+}
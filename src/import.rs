@@ -0,0 +1,193 @@
+use ::core::{
+	ffi::CStr,
+	ops::Range,
+};
+#[cfg(target_arch = "x86_64")]
+use ::std::collections::HashMap;
+
+use crate::{
+	os::unix::{
+		DynamicSymbol, Relocation, RelocationKind,
+	},
+	snapshot::{
+		ObjectSnapshot, OwnedObject,
+	},
+	Object,
+};
+#[cfg(target_arch = "x86_64")]
+use crate::{
+	scan::{
+		is_code_segment, segment_bytes,
+	},
+	x86_64::{
+		Instruction, OpcodeMap,
+	},
+};
+
+/// Dynamic relocation of an object, along with the value that it was resolved to
+/// and the loaded object that this value belongs to.
+/// 
+/// See [`Object::resolved_relocations`].
+#[derive(Debug, Clone)]
+pub struct ResolvedRelocation<'a> {
+	relocation: Relocation<'a>,
+	value: Option<usize>,
+	is_unresolved: bool,
+	provider: Option<&'a OwnedObject>,
+}
+
+impl<'a> ResolvedRelocation<'a> {
+	/// Returns the underlying relocation.
+	pub const fn relocation(&self) -> &Relocation<'a> {
+		&self.relocation
+	}
+
+	pub const fn kind(&self) -> RelocationKind {
+		self.relocation.kind()
+	}
+
+	/// Returns the absolute address that the relocation is applied to,
+	/// which is the GOT entry for imports.
+	pub const fn addr(&self) -> usize {
+		self.relocation.addr()
+	}
+
+	/// Returns the symbol of the relocation, if it has one.
+	pub fn symbol(&self) -> Option<DynamicSymbol<'a>> {
+		self.relocation.symbol()
+	}
+
+	/// Returns the name of the symbol of the relocation, if it has one.
+	pub fn name(&self) -> Option<&'a CStr> {
+		self.symbol().map(move |symbol| symbol.name())
+	}
+
+	/// Returns the address that the relocation currently resolves to.
+	/// 
+	/// This is the value at [`addr`](Self::addr) for relocations that store an address,
+	/// and [`addr`](Self::addr) itself for [`Copy`](RelocationKind::Copy) relocations,
+	/// whose data was copied there.
+	/// Relocations that don't resolve to an address, such as the TLS ones, return `None`.
+	pub const fn value(&self) -> Option<usize> {
+		self.value
+	}
+
+	/// Returns `true` if the relocation is a lazily bound [`JumpSlot`](RelocationKind::JumpSlot)
+	/// that hasn't been resolved yet, in which case its value is the address of a PLT stub.
+	pub const fn is_unresolved(&self) -> bool {
+		self.is_unresolved
+	}
+
+	/// Returns the loaded object that the [`value`](Self::value) of the relocation falls into,
+	/// which is the one that satisfied the import.
+	pub const fn provider(&self) -> Option<&'a OwnedObject> {
+		self.provider
+	}
+}
+
+/// Returns an iterator over the dynamic relocations of `object`,
+/// attributing their values to the objects in `objects`.
+pub(crate) fn resolved_relocations<'a>(
+	object: &'a Object<'_>,
+	objects: &'a ObjectSnapshot,
+) -> impl Iterator<Item = ResolvedRelocation<'a>> + use<'a> {
+	let load_range = object.as_unix_object().load_range();
+	object.dynamic()
+		.into_iter()
+		.flat_map(move |dynamic| dynamic.relocations())
+		.map(move |relocation| resolve(relocation, &load_range, objects))
+}
+
+fn resolve<'a>(relocation: Relocation<'a>, load_range: &Range<usize>, objects: &'a ObjectSnapshot) -> ResolvedRelocation<'a> {
+	let value = match relocation.kind() {
+		RelocationKind::Absolute | RelocationKind::GlobDat | RelocationKind::JumpSlot
+			| RelocationKind::Relative | RelocationKind::IRelative => {
+			// SAFETY: These relocations are applied to words in the loaded segments of the object.
+			Some(unsafe { relocation.value() })
+		}
+		RelocationKind::Copy => Some(relocation.addr()),
+		_ => None,
+	};
+	let is_unresolved = value.is_some_and(|value| relocation.is_unresolved(value, load_range));
+	let provider = match value {
+		Some(value) if !is_unresolved => objects.object_containing(value),
+		_ => None,
+	};
+	ResolvedRelocation {
+		relocation,
+		value,
+		is_unresolved,
+		provider,
+	}
+}
+
+/// PLT stub of an object, which jumps to an imported function through its GOT entry.
+/// 
+/// See [`Object::plt_stubs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PltStub<'a> {
+	addr: usize,
+	slot: usize,
+	name: &'a CStr,
+}
+
+impl<'a> PltStub<'a> {
+	/// Returns the absolute address of the stub, which is the one that calls to the import branch to.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	/// Returns the absolute address of the GOT entry that the stub jumps through.
+	pub const fn slot(&self) -> usize {
+		self.slot
+	}
+
+	/// Returns the name of the imported symbol.
+	pub const fn name(&self) -> &'a CStr {
+		self.name
+	}
+}
+
+/// Finds the indirect `jmp [rip + disp32]` instructions in the executable segments of `object`
+/// that jump through the GOT entry of an import, sorted by address.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn plt_stubs<'a>(object: &'a Object<'_>) -> Vec<PltStub<'a>> {
+	let Some(dynamic) = object.dynamic() else {
+		return Vec::new()
+	};
+	let slots: HashMap<usize, &CStr> = dynamic.relocations()
+		.filter(move |relocation| matches!(relocation.kind(), RelocationKind::JumpSlot | RelocationKind::GlobDat))
+		.filter_map(move |relocation| Some((relocation.addr(), relocation.symbol()?.name())))
+		.collect();
+	let mut stubs = Vec::new();
+	for segment in object.segments().filter(is_code_segment) {
+		// SAFETY: See `Scan::next`.
+		let data = unsafe { segment_bytes(&segment) };
+		for offset in ::memchr::memmem::find_iter(data, b"\xff\x25") {
+			let Some(instruction) = Instruction::decode(&data[offset..], segment.addr() + offset) else {
+				continue
+			};
+			if instruction.map() != OpcodeMap::Primary || instruction.opcode() != 0xff {
+				continue
+			}
+			let Some((slot, name)) = instruction.rip_target().and_then(|slot| Some((slot, *slots.get(&slot)?))) else {
+				continue
+			};
+			// Include the `bnd` prefix and the `endbr64` of stubs built for CET.
+			let mut start = offset;
+			if start >= 1 && data[start - 1] == 0xf2 {
+				start -= 1;
+			}
+			if start >= 4 && data[start - 4..start] == [0xf3, 0x0f, 0x1e, 0xfa] {
+				start -= 4;
+			}
+			stubs.push(PltStub {
+				addr: segment.addr() + start,
+				slot,
+				name,
+			});
+		}
+	}
+	stubs.sort_by_key(PltStub::addr);
+	stubs
+}
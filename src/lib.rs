@@ -1,11 +1,37 @@
 #![allow(dead_code)]
 
-use ::core::ffi::CStr;
+use ::core::{
+	ffi::CStr,
+	ops::Range,
+};
 
 pub mod util;
 
 pub mod map;
 use map::*;
+pub mod snapshot;
+use snapshot::*;
+pub mod addr;
+use addr::*;
+pub mod watch;
+pub mod scan;
+use scan::*;
+#[cfg(target_arch = "x86_64")]
+pub mod x86_64;
+pub mod xref;
+use xref::*;
+#[cfg(unix)]
+pub mod rtti;
+#[cfg(unix)]
+pub mod import;
+#[cfg(unix)]
+pub mod near;
+#[cfg(all(unix, target_arch = "x86_64"))]
+pub mod detour;
+#[cfg(all(unix, target_arch = "x86_64"))]
+pub mod hook;
+#[cfg(feature = "demangle")]
+pub mod demangle;
 pub mod os;
 use os::*;
 
@@ -13,6 +39,33 @@ use os::*;
 #[error("{0}")]
 #[repr(transparent)]
 pub struct Error(imp::Error);
+impl Error {
+	pub fn kind(&self) -> ErrorKind {
+		ErrorImpl::kind(&self.0)
+	}
+
+	/// Returns the underlying platform-specific error.
+	pub fn as_inner(&self) -> &imp::Error {
+		&self.0
+	}
+}
+
+/// General category of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+	/// The requested object or symbol doesn't exist.
+	NotFound,
+	/// The dynamic loader reported an error, such as with `dlerror` on Unix.
+	Loader,
+	/// An operating system call failed.
+	Os,
+	/// The object's in-memory image doesn't have the expected structure.
+	Malformed,
+	/// The operation can't be performed on the given code or data,
+	/// such as an instruction that can't be relocated.
+	Unsupported,
+}
 
 #[derive(Debug)]
 #[repr(transparent)]
@@ -30,16 +83,285 @@ impl Object<'_> {
 		Segments(ObjectImpl::segments(&self.0))
 	}
 
-	pub fn symbols(&self) -> Symbols {
-		Symbols(ObjectImpl::symbols(&self.0))
+	/// Returns a handle that can be used to look up symbols in the object with the platform's loader.
+	/// 
+	/// # Platform usage
+	/// On Unix, this uses `dlopen` with `RTLD_NOLOAD`,
+	/// which fails for objects that the loader can't find by name,
+	/// such as the vDSO or objects in other link map namespaces.
+	/// [`lookup_symbol`](Self::lookup_symbol) can be used for those instead.
+	pub fn symbols(&self) -> Result<Symbols, Error> {
+		match ObjectImpl::symbols(&self.0) {
+			Ok(symbols) => Ok(Symbols(symbols)),
+			Err(inner) => Err(Error(inner)),
+		}
 	}
 
 	pub fn symbol(&self, symbols: &Symbols, name: &CStr) -> *mut () {
 		ObjectImpl::symbol(&self.0, &symbols.0, name)
 	}
 
-	pub fn library(&self, symbols: Symbols) -> Library {
-		Library(ObjectImpl::library(&self.0, symbols.0))
+	/// Finds the exported symbol named `name` without changing the object's reference count,
+	/// returning a null pointer if it doesn't exist.
+	/// 
+	/// # Platform usage
+	/// On Unix, this walks the object's `DT_GNU_HASH` or `DT_HASH` table directly,
+	/// so it doesn't go through the loader or touch the `dlerror` state.
+	/// For `STT_GNU_IFUNC` symbols, the address of the resolver is returned.
+	/// On Windows, this uses `GetProcAddress`.
+	pub fn lookup_symbol(&self, name: &CStr) -> *mut () {
+		ObjectImpl::lookup_symbol(&self.0, name)
+	}
+
+	/// Returns an iterator over the absolute addresses of all matches of `pattern`
+	/// in the readable and executable segments of the object.
+	pub fn scan<'p>(&self, pattern: &'p Pattern<'p>) -> Scan<'_, 'p> {
+		Scan::new(pattern, self.segments())
+	}
+
+	/// Returns the absolute address of the first match of `pattern`
+	/// in the readable and executable segments of the object.
+	pub fn scan_first(&self, pattern: &Pattern<'_>) -> Option<usize> {
+		self.scan(pattern).next()
+	}
+
+	/// Finds the NUL-terminated string `bytes` in the read-only segments of the object,
+	/// returning its absolute address.
+	/// 
+	/// Only whole strings are matched, so `bytes` must not contain the terminating NUL,
+	/// and the string must be preceded by a NUL or the start of a segment.
+	/// Non-executable segments are searched before executable ones.
+	pub fn find_string(&self, bytes: &[u8]) -> Option<usize> {
+		let mut needle = Vec::with_capacity(bytes.len() + 1);
+		needle.extend_from_slice(bytes);
+		needle.push(0);
+		let finder = ::memchr::memmem::Finder::new(&needle);
+		let is_read_only = move |segment: &Segment<'_>| is_data_segment(segment) && !segment.flags().has_w();
+		let non_executable = self.segments().filter(move |segment| is_read_only(segment) && !segment.flags().has_x());
+		let executable = self.segments().filter(move |segment| is_read_only(segment) && segment.flags().has_x());
+		non_executable.chain(executable).find_map(|segment| {
+			// SAFETY: Readable loadable segments are mapped for as long as their object is loaded.
+			let data = unsafe { segment_bytes(&segment) };
+			finder.find_iter(data)
+				.find(move |&offset| offset == 0 || data[offset - 1] == 0)
+				.map(move |offset| segment.addr() + offset)
+		})
+	}
+
+	/// Returns the absolute range of addresses of the function that contains `addr`.
+	/// 
+	/// # Platform usage
+	/// On Unix, this uses the binary search table of `.eh_frame_hdr`
+	/// and the frame description entries in `.eh_frame`,
+	/// so it only finds functions that have unwind information.
+	/// On Windows, this is not supported yet and always returns `None`.
+	pub fn function_containing(&self, addr: usize) -> Option<Range<usize>> {
+		ObjectImpl::function_containing(&self.0, addr)
+	}
+
+	/// Returns an iterator over the absolute ranges of addresses of all functions in the object
+	/// that [`function_containing`](Self::function_containing) can find.
+	pub fn functions(&self) -> Functions<'_> {
+		Functions(ObjectImpl::functions(&self.0))
+	}
+
+	pub(crate) fn function_start(&self, addr: usize) -> Option<usize> {
+		self.function_containing(addr).map(move |range| range.start)
+	}
+
+	/// Returns an owned handle to the object that keeps it loaded.
+	pub fn library(&self, symbols: Symbols) -> Result<Library, Error> {
+		match ObjectImpl::library(&self.0, symbols.0) {
+			Ok(library) => Ok(Library(library)),
+			Err(inner) => Err(Error(inner)),
+		}
+	}
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Object<'_> {
+	/// Decodes the instruction at `addr`,
+	/// which must be in one of the readable and executable segments of the object.
+	/// 
+	/// This is usually used on the result of [`scan`](Self::scan) to follow the branch or
+	/// resolve the RIP-relative address at the match, with [`x86_64::Instruction::target`].
+	pub fn instruction_at(&self, addr: usize) -> Option<x86_64::Instruction> {
+		let segment = self.segments().find(move |segment| is_code_segment(segment) && segment.contains(addr))?;
+		// SAFETY: See `Scan::next`.
+		let data = unsafe { segment_bytes(&segment) };
+		x86_64::Instruction::decode(&data[addr - segment.addr()..], addr)
+	}
+
+	/// Returns an iterator that decodes the consecutive instructions starting at `addr`,
+	/// which must be in one of the readable and executable segments of the object.
+	pub fn instructions_from(&self, addr: usize) -> x86_64::Instructions<'_> {
+		let data = self.segments()
+			.find(move |segment| is_code_segment(segment) && segment.contains(addr))
+			// SAFETY: See `Scan::next`.
+			.map(move |segment| unsafe { &segment_bytes(&segment)[addr - segment.addr()..] })
+			.unwrap_or_default();
+		x86_64::Instructions::new(data, addr)
+	}
+
+	/// Follows the relative branch at `addr`, returning its absolute target.
+	pub fn follow_branch(&self, addr: usize) -> Option<usize> {
+		self.instruction_at(addr)?.branch_target()
+	}
+
+	/// Resolves the RIP-relative memory operand of the instruction at `addr` into an absolute address.
+	pub fn resolve_rip_relative(&self, addr: usize) -> Option<usize> {
+		self.instruction_at(addr)?.rip_target()
+	}
+
+	/// Finds the RIP-relative `lea` and `mov` instructions in the executable segments of the object
+	/// that reference `addr`, such as the one of a string found with [`find_string`](Self::find_string).
+	pub fn string_xrefs(&self, addr: usize) -> Vec<Xref> {
+		rip_relative_xrefs(self, addr, is_lea_or_mov)
+	}
+
+	/// Finds the `call rel32` and `jmp rel32` instructions in the executable segments of the object
+	/// that branch to `target`.
+	/// 
+	/// Calls through the PLT or through pointers are not found,
+	/// since they don't branch to `target` directly.
+	pub fn call_xrefs(&self, target: usize) -> Vec<Xref> {
+		xref::call_xrefs(self, target)
+	}
+}
+
+#[cfg(unix)]
+impl Object<'_> {
+	/// Returns the underlying `dl_phdr_info` of the object.
+	pub fn as_unix_object(&self) -> &os::unix::UnixObject {
+		self.0.as_unix_object()
+	}
+
+	/// Returns an iterator over the ELF notes in the object's `PT_NOTE` segments.
+	pub fn notes(&self) -> os::unix::Notes<'_> {
+		self.as_unix_object().notes()
+	}
+
+	/// Returns the GNU build ID of the object, if it has one.
+	pub fn build_id(&self) -> Option<&[u8]> {
+		self.as_unix_object().build_id()
+	}
+
+	/// Returns the dynamic section of the object, if it has one.
+	pub fn dynamic(&self) -> Option<os::unix::DynamicSection<'_>> {
+		self.as_unix_object().dynamic()
+	}
+
+	/// Allocates a block of at least `len` bytes of `kind` memory
+	/// within reach of `rel32` displacements from all of the object's loadable segments,
+	/// using the [global](near::NearAllocator::global) allocator.
+	pub fn alloc_near(&self, len: usize, kind: near::MemoryKind) -> Result<near::Block, Error> {
+		near::NearAllocator::global().alloc(self.as_unix_object().load_range(), len, kind)
+	}
+
+	/// Finds the symbol named `name` with the given `version` (such as `GLIBC_2.2.5`),
+	/// returning a null pointer if it doesn't exist.
+	/// 
+	/// Like [`lookup_symbol`](Self::lookup_symbol), this walks the symbol hash table directly,
+	/// but it can also find non-default versions of the symbol.
+	pub fn lookup_symbol_versioned(&self, name: &CStr, version: &CStr) -> *mut () {
+		match self.as_unix_object().lookup_symbol_versioned(name, version) {
+			Some(symbol) => symbol.addr() as _,
+			None => ::core::ptr::null_mut(),
+		}
+	}
+
+	/// Returns an iterator over the dynamic symbol table of the object,
+	/// which includes both the symbols that it exports and the ones that it imports.
+	pub fn dynamic_symbols(&self) -> Option<os::unix::DynamicSymbols<'_>> {
+		self.as_unix_object().dynamic_symbols()
+	}
+
+	/// Redirects the object's imports of the symbol named `name` to `replacement`
+	/// by rewriting their GOT entries, returning a guard that restores them when dropped.
+	/// 
+	/// Entries in `PT_GNU_RELRO` are made writable only for as long as they are being rewritten.
+	/// The original implementation can be called through [`ImportHook::original`](os::unix::ImportHook::original).
+	/// 
+	/// # Safety
+	/// `replacement` must be compatible with the imported symbol, such as a function with the same signature,
+	/// and no other code may change the protection of the object's `PT_GNU_RELRO` pages
+	/// while the guard is being created or dropped.
+	pub unsafe fn hook_import(&self, name: &CStr, replacement: *const ()) -> Result<os::unix::ImportHook, Error> {
+		let symbols = self.symbols()?;
+		unsafe { os::unix::ImportHook::new(self.as_unix_object(), symbols.0, name, replacement as usize) }.map_err(Error)
+	}
+
+	/// Returns an iterator over the dynamic relocations of the object,
+	/// along with the addresses they resolved to and the objects in `objects` that these belong to.
+	/// 
+	/// This includes relocations without symbols, such as relative ones.
+	/// Packed relative relocations (`DT_RELR`) are not included.
+	pub fn resolved_relocations<'a>(&'a self, objects: &'a ObjectSnapshot) -> impl Iterator<Item = import::ResolvedRelocation<'a>> + use<'a> {
+		import::resolved_relocations(self, objects)
+	}
+
+	/// Returns an iterator over the GOT entries of the symbols that the object imports,
+	/// along with the objects in `objects` that satisfied them.
+	/// 
+	/// See [`resolved_relocations`](Self::resolved_relocations).
+	pub fn imports<'a>(&'a self, objects: &'a ObjectSnapshot) -> impl Iterator<Item = import::ResolvedRelocation<'a>> + use<'a> {
+		self.resolved_relocations(objects).filter(move |relocation| {
+			matches!(relocation.kind(), os::unix::RelocationKind::JumpSlot | os::unix::RelocationKind::GlobDat)
+				&& relocation.symbol().is_some()
+		})
+	}
+
+	/// Finds the C++ class named `name`, such as `foo::Bar`, through its Itanium ABI RTTI,
+	/// returning its `type_info` and the vtables that the object has for it.
+	/// 
+	/// This doesn't need any symbols, since the `typeinfo name` string of the class
+	/// is searched for in the object's read-only data instead.
+	/// See [`rtti::mangle_class_name`] for the names that are supported.
+	pub fn find_class(&self, name: &str) -> Option<rtti::ClassInfo<'_>> {
+		self.find_class_mangled(&rtti::mangle_class_name(name)?)
+	}
+
+	/// Like [`find_class`](Self::find_class), but takes the mangled name of the class,
+	/// as it appears in its `typeinfo name` string (such as `N3foo3BarE`).
+	pub fn find_class_mangled(&self, mangled: &[u8]) -> Option<rtti::ClassInfo<'_>> {
+		rtti::find_class(self, mangled)
+	}
+}
+
+#[cfg(all(unix, target_arch = "x86_64"))]
+impl Object<'_> {
+	/// Finds the PLT stubs of the object, which jump to imported functions through their GOT entries,
+	/// sorted by address.
+	/// 
+	/// This maps the targets of calls to imports back to the names of the imported functions.
+	pub fn plt_stubs(&self) -> Vec<import::PltStub<'_>> {
+		import::plt_stubs(self)
+	}
+
+	/// Prepares an inline hook that redirects the function at `target` in the object to `detour`,
+	/// which must be [enabled](detour::Detour::enable) to take effect.
+	/// 
+	/// `target` must be in one of the object's executable segments,
+	/// and the instructions that are overwritten are relocated into a trampoline near the object.
+	/// 
+	/// # Safety
+	/// `target` must be the start of a function with the signature `F`.
+	pub unsafe fn detour<F: detour::FnPtr>(&self, target: usize, detour: F) -> Result<detour::Detour<F>, Error> {
+		unsafe { detour::Detour::new(self, target, detour) }
+	}
+
+	/// Adds `hook` to the process-wide chain of hooks of the function at `target` in the object,
+	/// in which hooks with a higher `priority` are called first.
+	/// 
+	/// Unlike separate [detours](Self::detour) of the same function,
+	/// the hooks in a chain can be removed in any order.
+	/// See [`hook::ChainedHook`] for how hooks call the rest of the chain.
+	/// 
+	/// # Safety
+	/// `target` must be the start of a function with the signature `F`,
+	/// which must not be patched by anything other than its chain.
+	pub unsafe fn hook_chained<F: detour::FnPtr>(&self, target: usize, hook: F, priority: i32) -> Result<hook::ChainedHook<F>, Error> {
+		unsafe { hook::hook(self, target, hook, priority) }
 	}
 }
 
@@ -60,8 +382,30 @@ impl Library {
 	}
 }
 
+#[cfg(unix)]
+impl Library {
+	/// Finds the symbol named `name` with the given `version` (such as `GLIBC_2.2.5`) using `dlvsym`.
+	pub fn symbol_versioned(&self, name: &CStr, version: &CStr) -> *mut () {
+		self.0.symbol_versioned(name, version) as _
+	}
+}
+
+#[cfg(unix)]
+impl Symbols {
+	/// Finds the symbol named `name` with the given `version` (such as `GLIBC_2.2.5`) using `dlvsym`.
+	pub fn symbol_versioned(&self, name: &CStr, version: &CStr) -> *mut () {
+		self.0.symbol_versioned(name, version) as _
+	}
+}
+
 #[repr(transparent)]
 pub struct Segments<'a>(imp::Segments<'a>);
+impl<'a> Segments<'a> {
+	/// Returns an iterator over only the segments that are mapped into memory.
+	pub fn loadable(self) -> LoadableSegments<'a> {
+		LoadableSegments(self)
+	}
+}
 impl<'a> Iterator for Segments<'a> {
 	type Item = Segment<'a>;
 	fn next(&mut self) -> Option<Self::Item> {
@@ -69,20 +413,128 @@ impl<'a> Iterator for Segments<'a> {
 	}
 }
 
+/// Iterator over the loadable segments of an object.
+/// 
+/// See [`Segments::loadable`].
+#[repr(transparent)]
+pub struct LoadableSegments<'a>(Segments<'a>);
+impl<'a> Iterator for LoadableSegments<'a> {
+	type Item = Segment<'a>;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.find(Segment::is_loadable)
+	}
+}
+
+/// Iterator over the functions of an object.
+/// 
+/// See [`Object::functions`].
+#[repr(transparent)]
+pub struct Functions<'a>(imp::Functions<'a>);
+impl Iterator for Functions<'_> {
+	type Item = Range<usize>;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.next()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.0.size_hint()
+	}
+}
+
 #[repr(transparent)]
 pub struct Segment<'a>(imp::Segment<'a>);
 impl Segment<'_> {
+	pub fn kind(&self) -> SegmentKind {
+		SegmentImpl::kind(&self.0)
+	}
+
+	/// Returns `true` if the segment is mapped into memory when the object is loaded.
+	pub fn is_loadable(&self) -> bool {
+		self.kind() == SegmentKind::Load
+	}
+
 	pub fn flags(&self) -> SegmentFlags {
 		SegmentFlags(self.0.flags())
 	}
 
-	pub fn virtual_addr(&self) -> usize {
-		SegmentImpl::virtual_addr(&self.0)
+	/// Returns the offset of the segment's data in the object's file.
+	pub fn file_offset(&self) -> usize {
+		SegmentImpl::file_offset(&self.0)
+	}
+
+	/// Returns the size of the segment's data in the object's file (`p_filesz`),
+	/// which may be smaller than its [`size`](Self::size) in memory.
+	pub fn file_size(&self) -> usize {
+		SegmentImpl::file_size(&self.0)
 	}
 
+	/// Returns the size of the segment in memory (`p_memsz`).
 	pub fn size(&self) -> usize {
 		SegmentImpl::size(&self.0)
 	}
+
+	/// Returns the address of the segment relative to the base address of its object.
+	pub fn virtual_addr(&self) -> usize {
+		SegmentImpl::virtual_addr(&self.0)
+	}
+
+	/// Returns the alignment of the segment in memory and in the object's file.
+	pub fn align(&self) -> usize {
+		SegmentImpl::align(&self.0)
+	}
+
+	/// Returns the absolute address of the start of the segment,
+	/// which is the object's base address plus the segment's [`virtual_addr`](Self::virtual_addr).
+	pub fn addr(&self) -> usize {
+		SegmentImpl::base_addr(&self.0).wrapping_add(self.virtual_addr())
+	}
+
+	/// Returns the absolute range of addresses that the segment occupies in memory.
+	pub fn addr_range(&self) -> Range<usize> {
+		let start = self.addr();
+		start..start.wrapping_add(self.size())
+	}
+
+	/// Returns `true` if `addr` is in the [`addr_range`](Self::addr_range) of the segment.
+	pub fn contains(&self, addr: usize) -> bool {
+		addr.wrapping_sub(self.addr()) < self.size()
+	}
+}
+
+/// Kind of a [`Segment`].
+/// 
+/// # Platform usage
+/// On Unix, this corresponds to the `p_type` of the segment's ELF program header.
+/// On Windows, the whole module is a single [`Load`](SegmentKind::Load) segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SegmentKind {
+	/// Unused entry (`PT_NULL`).
+	Null,
+	/// Segment that is mapped into memory (`PT_LOAD`).
+	Load,
+	/// Dynamic linking information (`PT_DYNAMIC`).
+	Dynamic,
+	/// Path to the program interpreter (`PT_INTERP`).
+	Interp,
+	/// Auxiliary information in ELF notes (`PT_NOTE`).
+	Note,
+	/// Reserved (`PT_SHLIB`).
+	Shlib,
+	/// Location of the program header table itself (`PT_PHDR`).
+	Phdr,
+	/// Thread-local storage template (`PT_TLS`).
+	Tls,
+	/// Location of `.eh_frame_hdr` (`PT_GNU_EH_FRAME`).
+	GnuEhFrame,
+	/// Executable stack requirement (`PT_GNU_STACK`).
+	GnuStack,
+	/// Range made read-only after relocation (`PT_GNU_RELRO`).
+	GnuRelro,
+	/// Location of the GNU property note (`PT_GNU_PROPERTY`).
+	GnuProperty,
+	/// Any other platform-specific kind.
+	Other(u32),
 }
 
 #[repr(transparent)]
@@ -105,6 +557,13 @@ impl SegmentFlags {
 	}
 }
 
+#[cfg(unix)]
+impl SegmentFlags {
+	pub const fn as_unix_flags(&self) -> &os::unix::SegmentFlags {
+		&self.0
+	}
+}
+
 #[derive(Default)]
 #[repr(transparent)]
 pub struct Objects(imp::Objects);
@@ -144,12 +603,56 @@ impl Objects {
 	where
 		F: FnMut(&CStr, Object<'_>) -> Option<R>,
 	{
-		match ObjectsImpl::find_map(&self.0, move |name, object| f(name.as_c_str(), Object(object))) {
+		match ObjectsImpl::find_map(&self.0, move |name, object| f(ModuleNameImpl::as_c_str(&name), Object(object))) {
 			Ok(result) => Ok(result),
 			Err(inner) => Err(Error(inner)),
 		}
 	}
 
+	/// Finds the loaded object with a loadable segment that contains `addr`.
+	/// 
+	/// The nearest exported symbol is also looked up when the platform supports it.
+	pub fn object_containing(&self, addr: usize) -> Result<Option<AddressInfo>, Error> {
+		let found = self.find_map(move |name, object| {
+			let segment = object.segments().position(move |segment| segment.is_loadable() && segment.contains(addr))?;
+			Some((OwnedObject::new(name, &object), segment))
+		})?;
+		Ok(found.map(move |(object, segment)| {
+			let symbol = ObjectsImpl::nearest_symbol(&self.0, addr)
+				.map(move |(name, symbol_addr)| NearestSymbol::new(name, symbol_addr, addr.wrapping_sub(symbol_addr)));
+			AddressInfo::new(object, segment, addr, symbol)
+		}))
+	}
+
+	/// Finds the loaded object that contains the code of this crate.
+	/// 
+	/// This differs from the object of the caller when this crate is linked in dynamically,
+	/// so use [`current_object!`] to find the object of the calling code.
+	pub fn crate_object(&self) -> Result<Option<AddressInfo>, Error> {
+		self.object_containing(Self::crate_object as *const () as usize)
+	}
+
+	/// Finds the aligned pointer-sized values equal to `target`
+	/// in the writable and RELRO data segments of all loaded objects,
+	/// such as the places where a vtable or a global is stored.
+	/// 
+	/// Only objects that have at least one reference are returned.
+	pub fn pointer_xrefs(&self, target: usize) -> Result<Vec<ObjectXrefs>, Error> {
+		let mut found = Vec::new();
+		self.for_each(|name, object| {
+			let xrefs = xref::pointer_xrefs(&object, target);
+			if !xrefs.is_empty() {
+				found.push(ObjectXrefs::new(OwnedObject::new(name, &object), xrefs));
+			}
+		})?;
+		Ok(found)
+	}
+
+	/// Copies all of the currently loaded objects into an [`ObjectSnapshot`].
+	pub fn snapshot(&self) -> Result<ObjectSnapshot, Error> {
+		ObjectSnapshot::from_objects(self)
+	}
+
 	/// Iterates over all named loaded objects, calling `f` with them.
 	/// 
 	/// Unlike [`find_map`](Self::find_map),
@@ -160,13 +663,50 @@ impl Objects {
 		R: ForEachResult,
 		F: FnMut(&CStr, Object<'_>) -> R,
 	{
-		match ObjectsImpl::for_each(&self.0, move |name, object| f(name.as_c_str(), Object(object)).into_is_break()) {
+		match ObjectsImpl::for_each(&self.0, move |name, object| f(ModuleNameImpl::as_c_str(&name), Object(object)).into_is_break()) {
 			Ok(result) => Ok(result),
 			Err(inner) => Err(Error(inner)),
 		}
 	}
 }
 
+/// Finds the loaded object that contains the code which invokes the macro,
+/// as an [`AddressInfo`] with [`Objects::object_containing`].
+/// 
+/// Unlike [`Objects::crate_object`], this finds the object of the calling crate
+/// even when this crate is linked in dynamically or shared by several plugins.
+/// An existing [`Objects`] can be passed to query it instead of a new one.
+/// 
+/// ```
+/// let info = loaded::current_object!().unwrap().unwrap();
+/// assert!(info.segment().flags().has_x());
+/// ```
+#[macro_export]
+macro_rules! current_object {
+	() => {
+		$crate::current_object!(&$crate::Objects::new())
+	};
+	($objects:expr $(,)?) => {{
+		// Expanded in the calling crate, so its code ends up in the caller's object.
+		#[inline(never)]
+		fn current_object_marker() {}
+		$crate::Objects::object_containing($objects, current_object_marker as *const () as ::core::primitive::usize)
+	}};
+}
+
+#[cfg(unix)]
+impl Objects {
+	/// Finds the dynamic type of the polymorphic C++ object at `ptr` through its vtable,
+	/// using the Itanium ABI.
+	/// 
+	/// The vtable pointer is read without faulting, and the vtable, its `type_info`
+	/// and the name of the class are checked to be in the data of loaded objects,
+	/// so this returns an error instead of crashing if `ptr` doesn't point to such an object.
+	pub fn dynamic_type(&self, ptr: *const ()) -> Result<rtti::DynamicType, Error> {
+		rtti::dynamic_type(self, ptr as usize)
+	}
+}
+
 /// Trait for values that can be returned in callbacks in [`Objects::for_each`].
 pub trait ForEachResult {
 	fn into_is_break(self) -> bool;
@@ -191,6 +731,21 @@ impl ForEachResult for ::core::ops::ControlFlow<(), ()> {
 mod tests {
 	use crate::*;
 
+	/// Loads the first library in `filenames` that isn't loaded yet, returning its filename and handle,
+	/// or `None` if none of them can be loaded, such as on musl.
+	#[cfg(unix)]
+	fn load_new_library(filenames: &[&'static CStr]) -> Option<(&'static CStr, *mut ::libc::c_void)> {
+		filenames.iter().find_map(|&filename| unsafe {
+			let loaded = ::libc::dlopen(filename.as_ptr(), ::libc::RTLD_NOW | ::libc::RTLD_NOLOAD);
+			if !loaded.is_null() {
+				::libc::dlclose(loaded);
+				return None
+			}
+			let handle = ::libc::dlopen(filename.as_ptr(), ::libc::RTLD_NOW);
+			(!handle.is_null()).then_some((filename, handle))
+		})
+	}
+
 	#[test]
 	fn has_main_program() {
 		let objects = Objects::new();
@@ -208,4 +763,679 @@ mod tests {
 		let objects = Objects::new();
 		assert_eq!(objects.map_by_name(c"\n", move |_| ()).unwrap(), None);
 	}
+
+	#[test]
+	fn snapshot_outlives_query() {
+		let mut snapshot = Objects::new().snapshot().unwrap();
+		snapshot.sort_by_base_addr();
+		assert!(snapshot.iter().any(OwnedObject::is_main_program));
+		assert!(snapshot.iter().zip(snapshot.iter().skip(1)).all(|(a, b)| a.base_addr() <= b.base_addr()));
+
+		let main = ::std::thread::spawn(move || {
+			snapshot.into_iter().find(OwnedObject::is_main_program).unwrap()
+		}).join().unwrap();
+		assert!(main.object().segments().count() > 0);
+	}
+
+	#[test]
+	fn main_program_has_segment_kinds() {
+		let objects = Objects::new();
+		objects.for_each(|_, object| {
+			if !object.is_main_program() {
+				return false
+			}
+			let mut segments = object.segments().loadable();
+			assert!(segments.all(|segment| segment.kind() == SegmentKind::Load && segment.file_size() <= segment.size()));
+			#[cfg(unix)]
+			assert!(object.segments().any(|segment| segment.kind() == SegmentKind::Dynamic));
+			true
+		}).unwrap();
+	}
+
+	#[test]
+	fn finds_object_containing_function() {
+		let objects = Objects::new();
+		let info = current_object!(&objects).unwrap().unwrap();
+		// This crate is linked in statically.
+		assert_eq!(objects.crate_object().unwrap().unwrap().object().base_addr(), info.object().base_addr());
+		assert!(info.segment().flags().has_x());
+		assert!(info.segment().addr_range().contains(&info.addr()));
+		assert_eq!(info.offset(), info.addr() - info.object().base_addr());
+
+		#[cfg(unix)]
+		{
+			let info = objects.object_containing(::libc::dladdr as *const () as usize).unwrap().unwrap();
+			let symbol = info.symbol().unwrap();
+			assert_eq!(symbol.name().to_bytes(), b"dladdr");
+			assert_eq!(symbol.offset(), 0);
+		}
+
+		assert!(objects.object_containing(0).unwrap().is_none());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn libc_has_notes() {
+		let objects = Objects::new();
+		objects.map_by_name(c"libc", |object| {
+			let object = object.as_unix_object();
+			assert!(object.notes().count() > 0);
+			assert!(object.build_id().is_some_and(|id| !id.is_empty()));
+			if let Some(tag) = object.abi_tag() {
+				assert_eq!(tag.os, 0);
+			}
+		}).unwrap().unwrap();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn libc_has_dynamic_section() {
+		let objects = Objects::new();
+		objects.map_by_name(c"libc", |object| {
+			let dynamic = object.dynamic().unwrap();
+			assert_eq!(dynamic.soname(), Some(c"libc.so.6"));
+			assert!(dynamic.needed().any(|name| name.to_bytes().starts_with(b"ld-linux")));
+			let symtab = dynamic.symtab().unwrap();
+			assert!(object.as_unix_object().load_range().contains(&symtab));
+		}).unwrap().unwrap();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn libc_exports_symbols() {
+		use crate::os::unix::SymbolKind;
+
+		let objects = Objects::new();
+		objects.map_by_name(c"libc", |object| {
+			let symbols = object.dynamic_symbols().unwrap();
+			assert!(symbols.len() > 1);
+			let dladdr = symbols.exported().find(|symbol| symbol.name() == c"dladdr").unwrap();
+			assert_eq!(dladdr.kind(), SymbolKind::Func);
+			assert_eq!(dladdr.addr(), ::libc::dladdr as *const () as usize);
+		}).unwrap().unwrap();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn looks_up_symbols_in_memory() {
+		let objects = Objects::new();
+		objects.map_by_name(c"libc", |object| {
+			let symbols = object.symbols().unwrap();
+			assert_eq!(object.lookup_symbol(c"dladdr"), object.symbol(&symbols, c"dladdr"));
+			assert!(object.lookup_symbol(c"this symbol does not exist").is_null());
+		}).unwrap().unwrap();
+		objects.map_by_name(c"linux-vdso", |object| {
+			assert!(!object.lookup_symbol(c"__vdso_clock_gettime").is_null());
+		}).unwrap();
+	}
+
+	#[cfg(all(target_os = "linux", target_env = "gnu", target_arch = "x86_64"))]
+	#[test]
+	fn finds_versioned_symbols() {
+		let objects = Objects::new();
+		objects.map_by_name(c"libc", |object| {
+			let dynamic = object.dynamic().unwrap();
+			assert!(dynamic.version_definitions().any(|definition| definition.name() == c"GLIBC_2.2.5"));
+			assert!(dynamic.version_requirements().any(|requirement| requirement.file().to_bytes().starts_with(b"ld-linux")));
+
+			let memcpy = object.dynamic_symbols().unwrap()
+				.filter(|symbol| symbol.name() == c"memcpy" && symbol.is_defined())
+				.filter_map(|symbol| symbol.version())
+				.collect::<Vec<_>>();
+			assert!(memcpy.iter().any(|version| version.name() == c"GLIBC_2.2.5" && version.is_hidden()));
+			assert!(memcpy.iter().any(|version| version.name() == c"GLIBC_2.14" && !version.is_hidden()));
+
+			let old = object.lookup_symbol_versioned(c"memcpy", c"GLIBC_2.2.5");
+			assert!(!old.is_null());
+			assert_eq!(object.symbols().unwrap().symbol_versioned(c"memcpy", c"GLIBC_2.2.5"), old);
+		}).unwrap().unwrap();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn symbols_dont_panic() {
+		let objects = Objects::new();
+		objects.for_each(|_, object| {
+			match object.symbols() {
+				Ok(symbols) => {
+					let library = object.library(symbols).unwrap();
+					assert_eq!(library.base_addr(), object.base_addr());
+				}
+				Err(error) => assert_ne!(error.kind(), ErrorKind::Os),
+			}
+		}).unwrap();
+	}
+
+	#[test]
+	fn parses_patterns() {
+		let pattern = Pattern::parse("48 8b ?? 4? ?0 ?").unwrap();
+		assert_eq!(pattern.bytes(), &[0x48, 0x8b, 0, 0x40, 0, 0]);
+		assert_eq!(pattern.mask(), &[0xff, 0xff, 0, 0xf0, 0x0f, 0]);
+		assert_eq!(pattern.to_string(), "48 8B ?? 4? ?0 ??");
+		assert_eq!(pattern.anchor(), (0, 2));
+		assert_eq!(Pattern::parse(""), Err(PatternError::Empty));
+		assert_eq!(Pattern::parse("48 8g"), Err(PatternError::InvalidToken { index: 1 }));
+
+		let data = [0x00, 0x48, 0x8b, 0x12, 0x45, 0x30, 0xff, 0x48, 0x8b, 0x12, 0x45];
+		assert_eq!(pattern.find_iter(&data).collect::<Vec<_>>(), [1]);
+		let code = Pattern::with_code_mask(&[0x48, 0x8b, 0xff], "xx?").unwrap();
+		assert_eq!(code.find_iter(&data).collect::<Vec<_>>(), [1, 7]);
+	}
+
+	#[test]
+	fn pattern_macro_matches_parse() {
+		static SIGNATURE: Pattern<'static> = pattern!("E8 ?? ?? ?? ?? 48 8b 4? ?f");
+		assert_eq!(SIGNATURE, Pattern::parse("E8 ?? ?? ?? ?? 48 8b 4? ?f").unwrap());
+		const CODE: Pattern<'static> = pattern!("  90\tcc ?  ");
+		assert_eq!(CODE.bytes(), &[0x90, 0xcc, 0]);
+	}
+
+	#[test]
+	fn pattern_set_matches_each_pattern() {
+		let set = PatternSet::new([
+			pattern!("48 8B ?? 4?"),
+			pattern!("8B 12"),
+			pattern!("?? ?? ff"),
+			pattern!("90 90 90"),
+		]).unwrap();
+		let data = [0x00, 0x48, 0x8b, 0x12, 0x45, 0x30, 0xff, 0x48, 0x8b, 0x12, 0x45];
+		let matches = set.scan_bytes(&data, 0x1000);
+		assert_eq!(matches.get(0), &[0x1001, 0x1007]);
+		assert_eq!(matches.unique(1), None);
+		assert_eq!(matches.unique(2), Some(0x1004));
+		assert_eq!(matches.ambiguous().collect::<Vec<_>>(), [0, 1]);
+		assert_eq!(matches.missing().collect::<Vec<_>>(), [3]);
+	}
+
+	#[cfg(target_arch = "x86_64")]
+	#[test]
+	fn decodes_instructions() {
+		use crate::x86_64::*;
+		let decode = |bytes: &[u8]| Instruction::decode(bytes, 0x1000).unwrap();
+
+		let call = decode(&[0xe8, 0xfb, 0xff, 0xff, 0xff]);
+		assert!(call.is_call());
+		assert_eq!(call.branch_target(), Some(0x1000));
+		assert_eq!(decode(&[0x74, 0x10]).branch_target(), Some(0x1012));
+		assert_eq!(decode(&[0x0f, 0x85, 0x00, 0x01, 0x00, 0x00]).branch_target(), Some(0x1106));
+
+		let lea = decode(&[0x48, 0x8d, 0x05, 0x10, 0x00, 0x00, 0x00]);
+		assert_eq!(lea.len(), 7);
+		assert_eq!(lea.rip_target(), Some(0x1017));
+		assert_eq!(lea.modrm_reg(), Some(0));
+
+		let mov = decode(&[0x48, 0x8b, 0x41, 0xf8]);
+		assert_eq!(mov.displacement(), Some(-8));
+		assert!(!mov.is_rip_relative());
+		assert_eq!(decode(&[0x4c, 0x8b, 0x84, 0x24, 0x00, 0x01, 0x00, 0x00]).displacement(), Some(0x100));
+
+		assert_eq!(decode(&[0x48, 0xb8, 1, 2, 3, 4, 5, 6, 7, 8]).len(), 10);
+		assert_eq!(decode(&[0x66, 0x81, 0x38, 0x34, 0x12]).immediate(), Some(0x1234));
+		assert_eq!(decode(&[0xc5, 0xf8, 0x77]).len(), 3);
+		assert_eq!(decode(&[0xc5, 0xfa, 0x6f, 0x05, 0, 0, 0, 0]).rip_target(), Some(0x1008));
+		assert_eq!(decode(&[0xf3, 0x0f, 0x1e, 0xfa]).len(), 4);
+		assert!(Instruction::decode(&[0xe8, 0x00], 0).is_none());
+	}
+
+	#[cfg(target_arch = "x86_64")]
+	#[test]
+	fn finds_string_xrefs() {
+		#[inline(never)]
+		fn uses_string() -> &'static ::core::ffi::CStr {
+			::std::hint::black_box(c"loaded: string cross-reference marker")
+		}
+		let objects = Objects::new();
+		let info = objects.object_containing(uses_string as *const () as usize).unwrap().unwrap();
+		let object = info.object().object();
+		let string = object.find_string(b"loaded: string cross-reference marker").unwrap();
+		assert_eq!(string, uses_string().as_ptr() as usize);
+		assert!(object.find_string(b"string cross-reference marker").is_none());
+
+		let xrefs = object.string_xrefs(string);
+		assert!(!xrefs.is_empty());
+		for xref in &xrefs {
+			assert_eq!(object.resolve_rip_relative(xref.addr()), Some(string));
+		}
+		#[cfg(unix)]
+		assert!(xrefs.iter().any(|xref| xref.function() == Some(uses_string as *const () as usize)));
+	}
+
+	#[test]
+	fn finds_pointer_xrefs() {
+		static POINTER: ::std::sync::atomic::AtomicUsize = ::std::sync::atomic::AtomicUsize::new(0);
+		let target = Box::into_raw(Box::new(0_u64)) as usize;
+		POINTER.store(target, ::std::sync::atomic::Ordering::SeqCst);
+		let found = Objects::new().pointer_xrefs(target).unwrap();
+		assert!(found.iter().flat_map(ObjectXrefs::xrefs).any(|xref| xref.addr() == POINTER.as_ptr() as usize));
+		for object_xrefs in &found {
+			for xref in object_xrefs.xrefs() {
+				let segment = object_xrefs.object().segments().nth(xref.segment_index()).unwrap();
+				assert!(segment.contains(xref.addr()));
+			}
+		}
+		drop(unsafe { Box::from_raw(target as *mut u64) });
+	}
+
+	#[cfg(target_arch = "x86_64")]
+	#[test]
+	fn finds_call_xrefs() {
+		#[inline(never)]
+		fn callee() -> u32 {
+			::std::hint::black_box(7)
+		}
+		#[inline(never)]
+		fn caller() -> u32 {
+			callee() + 1
+		}
+		let objects = Objects::new();
+		let info = objects.object_containing(callee as *const () as usize).unwrap().unwrap();
+		let xrefs = info.object().object().call_xrefs(callee as *const () as usize);
+		assert!(!xrefs.is_empty());
+		for xref in &xrefs {
+			assert_eq!(info.object().object().follow_branch(xref.addr()), Some(callee as *const () as usize));
+		}
+		assert_eq!(caller(), 8);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn finds_function_boundaries() {
+		#[inline(never)]
+		fn function() -> usize {
+			::std::hint::black_box(function as *const () as usize)
+		}
+		let objects = Objects::new();
+		let start = function();
+		let info = objects.object_containing(start).unwrap().unwrap();
+		let object = info.object().object();
+		let range = object.function_containing(start + 1).unwrap();
+		assert_eq!(range.start, start);
+		assert!(range.end > start + 1);
+		assert!(object.function_containing(0).is_none());
+
+		let functions: Vec<_> = object.functions().collect();
+		assert!(functions.contains(&range));
+		assert!(functions.windows(2).all(|pair| pair[0].start <= pair[1].start));
+		assert_eq!(object.as_unix_object().eh_frame_hdr().unwrap().len(), functions.len());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn finds_cxx_classes() {
+		assert_eq!(rtti::mangle_class_name("Foo").unwrap(), b"3Foo");
+		assert_eq!(rtti::mangle_class_name("foo::Bar").unwrap(), b"N3foo3BarE");
+		assert_eq!(rtti::mangle_class_name("std::ios_base::failure").unwrap(), b"NSt8ios_base7failureE");
+		assert!(rtti::mangle_class_name("Foo<int>").is_none());
+
+		let handle = unsafe { ::libc::dlopen(c"libstdc++.so.6".as_ptr(), ::libc::RTLD_NOW) };
+		if handle.is_null() {
+			return
+		}
+		Objects::new().map_by_name(c"libstdc++", |object| {
+			let class = object.find_class("std::runtime_error").unwrap();
+			assert_eq!(class.type_info().name(), c"St13runtime_error");
+			assert_eq!(class.type_info().addr(), object.lookup_symbol(c"_ZTISt13runtime_error") as usize);
+			let vtable = class.primary_vtable().unwrap();
+			assert_eq!(vtable.addr(), object.lookup_symbol(c"_ZTVSt13runtime_error") as usize + rtti::VTABLE_ADDRESS_POINT);
+			// Destructors and `what`.
+			assert!(vtable.slots() >= 3);
+
+			let bases = class.bases();
+			assert_eq!(bases.len(), 1);
+			assert_eq!(bases[0].type_info().name(), c"St9exception");
+			assert!(bases[0].type_info().bases().is_empty());
+
+			// `std::iostream` derives from both `std::istream` and `std::ostream`.
+			let iostream = unsafe {
+				rtti::TypeInfo::from_addr(object.lookup_symbol(c"_ZTISd") as usize, rtti::TypeInfoKind::VirtualMultipleInheritance)
+			};
+			assert_eq!(iostream.name(), c"Sd");
+			let bases = iostream.bases();
+			assert_eq!(bases.len(), 2);
+			assert_eq!(bases[0].type_info().name(), c"Si");
+			assert_eq!(bases[1].type_info().name(), c"So");
+			assert!(bases.iter().all(|base| base.is_public() && !base.is_virtual()));
+			assert_eq!(bases[0].type_info().kind(), rtti::TypeInfoKind::VirtualMultipleInheritance);
+			assert!(bases[0].type_info().bases()[0].is_virtual());
+		}).unwrap().unwrap();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn identifies_dynamic_types() {
+		assert_eq!(rtti::demangle_class_name(b"3Foo").unwrap(), "Foo");
+		assert_eq!(rtti::demangle_class_name(b"N3foo3BarE").unwrap(), "foo::Bar");
+		assert_eq!(rtti::demangle_class_name(b"St13runtime_error").unwrap(), "std::runtime_error");
+		assert_eq!(rtti::demangle_class_name(b"NSt8ios_base7failureE").unwrap(), "std::ios_base::failure");
+		assert!(rtti::demangle_class_name(b"3FooIiE").is_none());
+
+		let objects = Objects::new();
+		assert!(objects.dynamic_type(::core::ptr::null()).is_err());
+		assert!(objects.dynamic_type(8 as *const ()).is_err());
+		let not_an_object = [0_usize; 2];
+		assert_eq!(objects.dynamic_type(not_an_object.as_ptr() as _).unwrap_err().kind(), ErrorKind::NotFound);
+
+		let handle = unsafe { ::libc::dlopen(c"libstdc++.so.6".as_ptr(), ::libc::RTLD_NOW) };
+		if handle.is_null() {
+			return
+		}
+		// A fake object whose vtable pointer is that of `std::runtime_error`.
+		let vtable = objects.map_by_name(c"libstdc++", |object| {
+			object.lookup_symbol(c"_ZTVSt13runtime_error") as usize + rtti::VTABLE_ADDRESS_POINT
+		}).unwrap().unwrap();
+		let fake = [vtable, 0];
+		let dynamic_type = objects.dynamic_type(fake.as_ptr() as _).unwrap();
+		assert_eq!(dynamic_type.name(), "std::runtime_error");
+		assert_eq!(dynamic_type.offset_to_top(), 0);
+		assert_eq!(dynamic_type.top_addr(), fake.as_ptr() as usize);
+		assert!(dynamic_type.object().name().to_bytes().ends_with(b"libstdc++.so.6"));
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn hooks_imports() {
+		unsafe extern "C" fn fake_getppid() -> ::libc::pid_t {
+			-42
+		}
+		// Calls through the GOT entry every time, since it may otherwise be loaded only once.
+		#[inline(never)]
+		fn getppid() -> ::libc::pid_t {
+			unsafe { ::libc::getppid() }
+		}
+		let real = getppid();
+
+		let objects = Objects::new();
+		objects.find_map(|_, object| object.is_main_program().then(|| {
+			assert_eq!(unsafe { object.hook_import(c"not_an_import", fake_getppid as *const ()) }.unwrap_err().kind(), ErrorKind::NotFound);
+
+			let hook = unsafe { object.hook_import(c"getppid", fake_getppid as *const ()) }.unwrap();
+			assert!(!hook.slots().is_empty());
+			assert_eq!(getppid(), -42);
+			let original: unsafe extern "C" fn() -> ::libc::pid_t = unsafe { ::core::mem::transmute(hook.original()) };
+			assert_eq!(unsafe { original() }, real);
+
+			hook.restore().unwrap();
+			assert_eq!(getppid(), real);
+		})).unwrap().unwrap();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn attributes_imports() {
+		let objects = Objects::new();
+		let snapshot = objects.snapshot().unwrap();
+		objects.find_map(|_, object| object.is_main_program().then(|| {
+			let import = object.imports(&snapshot)
+				.find(|import| import.name() == Some(c"dl_iterate_phdr"))
+				.unwrap();
+			assert!(!import.is_unresolved());
+			let provider = import.provider().unwrap();
+			assert!(provider.name().to_str().unwrap().contains("libc"));
+			assert_eq!(import.value(), Some(::libc::dl_iterate_phdr as *const () as usize));
+
+			let relative = object.resolved_relocations(&snapshot)
+				.find(|relocation| relocation.kind() == os::unix::RelocationKind::Relative)
+				.unwrap();
+			assert!(relative.provider().unwrap().is_main_program());
+		})).unwrap().unwrap();
+
+		#[cfg(target_arch = "x86_64")]
+		objects.map_by_name(c"libc", |object| {
+			let stubs = object.plt_stubs();
+			let realloc = stubs.iter().find(|stub| stub.name() == c"realloc").unwrap();
+			assert!(object.dynamic().unwrap().import_slots(b"realloc").any(|slot| slot.addr() == realloc.slot()));
+			assert!(object.segments().any(|segment| segment.flags().has_x() && segment.contains(realloc.addr())));
+		}).unwrap().unwrap();
+	}
+
+	#[cfg(all(unix, target_arch = "x86_64"))]
+	#[test]
+	fn allocates_near_objects() {
+		use near::{
+			MemoryKind, NearAllocator,
+		};
+
+		let near = Objects::new().find_map(|_, object| {
+			object.is_main_program().then(|| object.as_unix_object().load_range())
+		}).unwrap().unwrap();
+		let allocator = NearAllocator::new();
+		let code = allocator.alloc(near.clone(), 6, MemoryKind::Code).unwrap();
+		assert!(code.addr().abs_diff(near.start) < 1 << 31 && code.range().end.abs_diff(near.end) < 1 << 31);
+		// `mov eax, 42; ret`
+		unsafe { code.write(0, &[0xb8, 42, 0, 0, 0, 0xc3]) }.unwrap();
+		let function: extern "C" fn() -> i32 = unsafe { ::core::mem::transmute(code.addr()) };
+		assert_eq!(function(), 42);
+
+		// The code is never writable where it's executable.
+		let maps = ::std::fs::read_to_string("/proc/self/maps").unwrap();
+		let perms = maps.lines().find_map(|line| {
+			let (range, rest) = line.split_once(' ')?;
+			let (start, end) = range.split_once('-')?;
+			let range = usize::from_str_radix(start, 16).ok()?..usize::from_str_radix(end, 16).ok()?;
+			range.contains(&code.addr()).then(|| rest[..4].to_owned())
+		}).unwrap();
+		assert_eq!(&perms[..3], "r-x");
+
+		let data = allocator.alloc(code.range(), 8, MemoryKind::Data).unwrap();
+		unsafe { data.write(0, &7_u64.to_ne_bytes()) }.unwrap();
+		assert_eq!(unsafe { *(data.addr() as *const u64) }, 7);
+
+		let next = allocator.alloc(near.clone(), 1, MemoryKind::Code).unwrap();
+		assert_eq!(next.addr(), code.addr() + near::BLOCK_ALIGN);
+		let addr = code.addr();
+		unsafe { allocator.free(code) }.unwrap();
+		let reused = allocator.alloc(near, 6, MemoryKind::Code).unwrap();
+		assert_eq!(reused.addr(), addr);
+		unsafe {
+			allocator.free(reused).unwrap();
+			allocator.free(next).unwrap();
+			allocator.free(data).unwrap();
+		}
+	}
+
+	#[cfg(all(unix, target_arch = "x86_64"))]
+	#[test]
+	fn detours_functions() {
+		use ::core::sync::atomic::{
+			AtomicUsize, Ordering,
+		};
+
+		static CALLS: AtomicUsize = AtomicUsize::new(0);
+		#[inline(never)]
+		extern "C" fn add(a: i32, b: i32) -> i32 {
+			CALLS.fetch_add(1, Ordering::SeqCst);
+			a.wrapping_add(b)
+		}
+		extern "C" fn multiply(a: i32, b: i32) -> i32 {
+			a.wrapping_mul(b)
+		}
+		let add_ptr = ::core::hint::black_box(add as extern "C" fn(i32, i32) -> i32);
+
+		let target = add as *const () as usize;
+		let info = Objects::new().object_containing(target).unwrap().unwrap();
+		let detour = unsafe { info.object().object().detour(target, multiply as extern "C" fn(i32, i32) -> i32) }.unwrap();
+		assert_eq!(add_ptr(2, 3), 5);
+
+		unsafe { detour.enable() }.unwrap();
+		assert!(detour.is_enabled());
+		assert_eq!(add_ptr(2, 3), 6);
+		let calls = CALLS.load(Ordering::SeqCst);
+		assert_eq!(unsafe { detour.call_original((2, 3)) }, 5);
+		assert_eq!(CALLS.load(Ordering::SeqCst), calls + 1);
+
+		unsafe { detour.disable() }.unwrap();
+		assert_eq!(add_ptr(2, 3), 5);
+		unsafe { detour.enable() }.unwrap();
+		drop(detour);
+		assert_eq!(add_ptr(2, 3), 5);
+	}
+
+	#[cfg(all(unix, target_arch = "x86_64"))]
+	#[test]
+	fn chains_hooks() {
+		use ::core::sync::atomic::{
+			AtomicUsize, Ordering,
+		};
+		use ::std::sync::Mutex;
+
+		type Hook = hook::ChainedHook<extern "C" fn(i32) -> i32>;
+		static CALLS: AtomicUsize = AtomicUsize::new(0);
+		static INNER: Mutex<Option<Hook>> = Mutex::new(None);
+		static OUTER: Mutex<Option<Hook>> = Mutex::new(None);
+		#[inline(never)]
+		extern "C" fn identity(x: i32) -> i32 {
+			CALLS.fetch_add(1, Ordering::SeqCst);
+			x
+		}
+		extern "C" fn inner(x: i32) -> i32 {
+			let next = INNER.lock().unwrap().as_ref().unwrap().next();
+			next(x) + 1
+		}
+		extern "C" fn outer(x: i32) -> i32 {
+			let next = OUTER.lock().unwrap().as_ref().unwrap().next();
+			next(x) * 10
+		}
+		let identity_ptr = ::core::hint::black_box(identity as extern "C" fn(i32) -> i32);
+
+		let target = identity as *const () as usize;
+		let info = Objects::new().object_containing(target).unwrap().unwrap();
+		let hook = |hook: extern "C" fn(i32) -> i32, priority| {
+			unsafe { info.object().object().hook_chained(target, hook, priority) }.unwrap()
+		};
+		*INNER.lock().unwrap() = Some(hook(inner, 0));
+		assert_eq!(identity_ptr(2), 3);
+		*OUTER.lock().unwrap() = Some(hook(outer, 10));
+		assert_eq!(identity_ptr(2), 30);
+		assert!(OUTER.lock().unwrap().as_ref().unwrap().is_linked());
+		assert_eq!(hook::prune().unwrap(), 0);
+
+		let inner = INNER.lock().unwrap().take().unwrap();
+		inner.remove().unwrap();
+		assert_eq!(identity_ptr(2), 20);
+		let calls = CALLS.load(Ordering::SeqCst);
+		drop(OUTER.lock().unwrap().take());
+		assert_eq!(identity_ptr(2), 2);
+		assert_eq!(CALLS.load(Ordering::SeqCst), calls + 1);
+	}
+
+	#[cfg(all(unix, target_arch = "x86_64"))]
+	#[test]
+	fn chained_hooks_follow_unloads() {
+		/// Returns `true` if the library at `filename` is still loaded.
+		fn is_loaded(filename: &CStr) -> bool {
+			unsafe {
+				let handle = ::libc::dlopen(filename.as_ptr(), ::libc::RTLD_NOW | ::libc::RTLD_NOLOAD);
+				if !handle.is_null() {
+					::libc::dlclose(handle);
+				}
+				!handle.is_null()
+			}
+		}
+		#[inline(never)]
+		extern "C" fn answer() -> usize {
+			::core::hint::black_box(42)
+		}
+		extern "C" fn fake_mb_cur_max() -> usize {
+			7
+		}
+		let objects = Objects::new();
+
+		// A hook keeps the object that contains its code loaded until it's removed.
+		if let Some((filename, handle)) = load_new_library(&[c"libutil.so.1"]) {
+			// The hook is never called, so any code in the library will do.
+			let hook_addr = objects.map_by_name(c"libutil", |object| {
+				object.segments().find(|segment| segment.flags().has_x()).unwrap().addr()
+			}).unwrap().unwrap();
+			let target = answer as *const () as usize;
+			let info = objects.object_containing(target).unwrap().unwrap();
+			let hook = unsafe {
+				let hook = <extern "C" fn() -> usize as detour::FnPtr>::from_addr(hook_addr);
+				info.object().object().hook_chained(target, hook, 0).unwrap()
+			};
+			unsafe { ::libc::dlclose(handle) };
+			assert!(is_loaded(filename));
+			assert!(hook.is_linked());
+			hook.remove().unwrap();
+			assert!(!is_loaded(filename));
+			assert_eq!(answer(), 42);
+		}
+
+		// The detour of a target keeps its object loaded until the last hook is removed.
+		if let Some((filename, handle)) = load_new_library(&[c"libBrokenLocale.so.1"]) {
+			let target = unsafe { ::libc::dlsym(handle, c"__ctype_get_mb_cur_max".as_ptr()) } as usize;
+			let target_fn: extern "C" fn() -> usize = unsafe { ::core::mem::transmute(target) };
+			let info = objects.object_containing(target).unwrap().unwrap();
+			let hook = unsafe { info.object().object().hook_chained(target, fake_mb_cur_max as extern "C" fn() -> usize, 0) }.unwrap();
+			drop(info);
+			unsafe { ::libc::dlclose(handle) };
+			assert!(is_loaded(filename));
+			assert_eq!(target_fn(), 7);
+			assert_eq!(hook::prune().unwrap(), 0);
+			assert!(hook.is_linked());
+			hook.remove().unwrap();
+			assert!(!is_loaded(filename));
+		}
+	}
+
+	#[cfg(feature = "demangle")]
+	#[test]
+	fn demangles_symbols() {
+		use crate::demangle::*;
+		assert_eq!(demangle("_ZN3foo3barEv").unwrap(), "foo::bar()");
+		assert_eq!(demangle("_ZN4core3fmt5write17h0123456789abcdefE").unwrap(), "core::fmt::write");
+		assert_eq!(demangle("_RNvCs1234_7mycrate3foo").unwrap(), "mycrate::foo");
+		assert!(demangle("memcpy").is_none());
+		assert_eq!(demangle_lossy(b"memcpy"), "memcpy");
+		assert_eq!(demangle_type_name(b"St6vectorIiSaIiEE").unwrap(), "std::vector<int, std::allocator<int> >");
+
+		#[cfg(unix)]
+		{
+			let info = Objects::new().object_containing(::libc::dladdr as *const () as usize).unwrap().unwrap();
+			assert_eq!(info.symbol().unwrap().demangled_name(), "dladdr");
+		}
+	}
+
+	#[test]
+	fn scans_own_code() {
+		fn marker() -> u64 {
+			::std::hint::black_box(0x1122_3344_5566_7788)
+		}
+		let bytes = 0x1122_3344_5566_7788_u64.to_ne_bytes();
+		let pattern = Pattern::new(&bytes, &[0xff; 8]);
+		let objects = Objects::new();
+		let info = objects.object_containing(marker as *const () as usize).unwrap().unwrap();
+		let found = info.object().object().scan_first(&pattern);
+		assert!(found.is_some_and(|addr| info.object().object().segments().any(|segment| segment.flags().is_rx() && segment.contains(addr))));
+
+		let set = PatternSet::new([pattern, pattern!("0F 0B CC CC CC CC CC CC CC CC CC CC CC CC CC CC")]).unwrap();
+		let object = info.object().object();
+		let matches = set.scan(&object);
+		assert_eq!(matches, set.scan_parallel(&object));
+		assert_eq!(matches.first(0), found);
+
+		#[cfg(target_arch = "x86_64")]
+		{
+			let movabs = object.instructions_from(found.unwrap() - 2).next().unwrap();
+			assert_eq!(movabs.immediate(), Some(0x1122_3344_5566_7788));
+		}
+		assert_eq!(marker(), 0x1122_3344_5566_7788);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn watcher_sees_loaded_object() {
+		use ::core::time::Duration;
+		use crate::watch::ObjectWatcher;
+
+		let mut watcher = ObjectWatcher::new().unwrap();
+
+		let Some((filename, handle)) = load_new_library(&[c"libresolv.so.2", c"libanl.so.1"]) else {
+			return
+		};
+		assert!(watcher.has_changed());
+		let name = ::std::ffi::CString::new(util::to_nice_name(filename.to_bytes())).unwrap();
+		let object = watcher.wait_for(&name, Duration::from_secs(1)).unwrap();
+		assert!(object.is_some());
+		unsafe { ::libc::dlclose(handle) };
+	}
 }
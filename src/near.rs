@@ -0,0 +1,244 @@
+use ::core::{
+	fmt,
+	ops::Range,
+	ptr::copy_nonoverlapping,
+};
+use ::libc::{
+	PROT_EXEC, PROT_READ, PROT_WRITE,
+};
+use ::std::sync::{
+	Mutex, MutexGuard,
+};
+
+use crate::{
+	os::unix::{
+		is_near, map_code_near, map_near, page_size, with_protection, NearMapping,
+	},
+	Error,
+};
+
+/// Smallest size of the mappings that blocks are allocated from.
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Alignment of the addresses and sizes of blocks.
+pub const BLOCK_ALIGN: usize = 16;
+
+/// Kind of memory that a [`Block`] is allocated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryKind {
+	/// Readable and executable memory, which is written to with [`Block::write`].
+	Code,
+	/// Readable and writable memory.
+	Data,
+}
+
+/// Mapping that blocks of one kind are allocated from.
+#[derive(Debug)]
+struct Chunk {
+	kind: MemoryKind,
+	mapping: NearMapping,
+	/// Free ranges of offsets into the mapping, sorted and never adjacent to each other.
+	free: Vec<Range<usize>>,
+}
+
+impl Chunk {
+	fn new(kind: MemoryKind, mapping: NearMapping) -> Self {
+		// Initially, the whole mapping is free.
+		let free = vec![Range { start: 0, end: mapping.len() }];
+		Self {
+			kind,
+			mapping,
+			free,
+		}
+	}
+
+	/// Takes the first free range of offsets that fits `len` bytes.
+	fn take(&mut self, len: usize) -> Option<usize> {
+		let index = self.free.iter().position(move |range| range.len() >= len)?;
+		let range = &mut self.free[index];
+		let offset = range.start;
+		range.start += len;
+		if range.start == range.end {
+			self.free.remove(index);
+		}
+		Some(offset)
+	}
+
+	/// Returns the `len` bytes at `offset` to the free ranges, merging them with their neighbors.
+	fn put(&mut self, offset: usize, len: usize) {
+		let index = self.free.partition_point(move |range| range.start < offset);
+		let merges_before = index > 0 && self.free[index - 1].end == offset;
+		let merges_after = self.free.get(index).is_some_and(move |range| range.start == offset + len);
+		match (merges_before, merges_after) {
+			(true, true) => {
+				self.free[index - 1].end = self.free[index].end;
+				self.free.remove(index);
+			}
+			(true, false) => self.free[index - 1].end += len,
+			(false, true) => self.free[index].start = offset,
+			(false, false) => self.free.insert(index, offset..offset + len),
+		}
+	}
+
+	fn is_unused(&self) -> bool {
+		self.free.first().is_some_and(|range| range.len() == self.mapping.len())
+	}
+
+	fn block(&self, offset: usize, len: usize) -> Block {
+		Block {
+			addr: self.mapping.addr() + offset,
+			len,
+			kind: self.kind,
+			alias: self.mapping.alias().map(move |alias| alias + offset),
+		}
+	}
+}
+
+/// Allocator of small blocks of memory within reach of `rel32` displacements (±2 GiB) from a range,
+/// such as the one of an object, for trampolines and code caves.
+/// 
+/// Blocks are carved out of larger mappings in the free address space around the range,
+/// which is found through `/proc/self/maps`.
+/// Mappings are shared by all of the ranges that they're within reach of,
+/// and are unmapped once all of their blocks are freed.
+/// 
+/// [Code](MemoryKind::Code) is never writable and executable at the same time,
+/// see [`Block::write`].
+/// 
+/// Blocks aren't freed when they're dropped, since code may still be running them,
+/// and neither are the blocks that are left when the allocator itself is dropped.
+#[derive(Debug, Default)]
+pub struct NearAllocator {
+	chunks: Mutex<Vec<Chunk>>,
+}
+
+impl NearAllocator {
+	pub const fn new() -> Self {
+		Self {
+			chunks: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Returns the process-wide allocator, which is also used for the trampolines of [detours](crate::detour).
+	pub fn global() -> &'static Self {
+		static GLOBAL: NearAllocator = NearAllocator::new();
+		&GLOBAL
+	}
+
+	fn chunks(&self) -> MutexGuard<'_, Vec<Chunk>> {
+		self.chunks.lock().unwrap_or_else(move |poisoned| poisoned.into_inner())
+	}
+
+	/// Allocates a block of at least `len` bytes of `kind` memory within reach of all of `near`,
+	/// such as the [`load_range`](crate::os::unix::UnixObject::load_range) of an object.
+	/// 
+	/// The block starts out filled with zeroes, unless it reuses memory that was freed.
+	pub fn alloc(&self, near: Range<usize>, len: usize, kind: MemoryKind) -> Result<Block, Error> {
+		let len = len.max(1).next_multiple_of(BLOCK_ALIGN);
+		let mut chunks = self.chunks();
+		for chunk in chunks.iter_mut().filter(|chunk| chunk.kind == kind && is_near(&near, &chunk.mapping.range())) {
+			if let Some(offset) = chunk.take(len) {
+				return Ok(chunk.block(offset, len))
+			}
+		}
+
+		let chunk_len = len.max(CHUNK_SIZE).next_multiple_of(page_size());
+		let mapping = match kind {
+			MemoryKind::Code => map_code_near(near, chunk_len),
+			MemoryKind::Data => map_near(near, chunk_len),
+		}.map_err(Error)?;
+		let mut chunk = Chunk::new(kind, mapping);
+		let offset = chunk.take(len).expect("new chunk should fit the block");
+		let block = chunk.block(offset, len);
+		chunks.push(chunk);
+		Ok(block)
+	}
+
+	/// Returns `block` to the allocator, unmapping the memory that it was in if it was the last one there.
+	/// 
+	/// # Safety
+	/// `block` must have been allocated by this allocator,
+	/// and nothing may use it afterwards, including code that is still running in it.
+	pub unsafe fn free(&self, block: Block) -> Result<(), Error> {
+		let mut chunks = self.chunks();
+		let Some(index) = chunks.iter().position(move |chunk| chunk.mapping.range().contains(&block.addr)) else {
+			return Ok(())
+		};
+		let chunk = &mut chunks[index];
+		chunk.put(block.addr - chunk.mapping.addr(), block.len);
+		if chunk.is_unused() {
+			let chunk = chunks.swap_remove(index);
+			unsafe { chunk.mapping.unmap() }.map_err(Error)?;
+		}
+		Ok(())
+	}
+}
+
+/// Block of memory that was allocated by a [`NearAllocator`].
+/// 
+/// The block stays allocated until it's passed to [`NearAllocator::free`].
+pub struct Block {
+	addr: usize,
+	len: usize,
+	kind: MemoryKind,
+	alias: Option<usize>,
+}
+
+impl Block {
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	/// Returns the size of the block, which is a multiple of [`BLOCK_ALIGN`].
+	pub const fn len(&self) -> usize {
+		self.len
+	}
+
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub const fn range(&self) -> Range<usize> {
+		self.addr..self.addr + self.len
+	}
+
+	pub const fn kind(&self) -> MemoryKind {
+		self.kind
+	}
+
+	/// Copies `bytes` into the block at `offset`.
+	/// 
+	/// Code is written through a writable view of the same memory where the platform supports it.
+	/// Otherwise, its pages are made writable but not executable for the duration of the write,
+	/// which means that other blocks of code on the same pages can't run in the meantime.
+	/// 
+	/// # Panics
+	/// Panics if `bytes` don't fit in the block at `offset`.
+	/// 
+	/// # Safety
+	/// Nothing may be running or reading the bytes that are overwritten.
+	pub unsafe fn write(&self, offset: usize, bytes: &[u8]) -> Result<(), Error> {
+		assert!(offset.checked_add(bytes.len()).is_some_and(|end| end <= self.len), "write is out of the bounds of the block");
+		let addr = self.addr + offset;
+		let copy = move |dest: usize| unsafe { copy_nonoverlapping(bytes.as_ptr(), dest as *mut u8, bytes.len()) };
+		match (self.kind, self.alias) {
+			(MemoryKind::Data, _) => copy(addr),
+			(MemoryKind::Code, Some(alias)) => copy(alias + offset),
+			(MemoryKind::Code, None) => unsafe {
+				with_protection(addr..addr + bytes.len(), PROT_READ | PROT_WRITE, PROT_READ | PROT_EXEC, move || copy(addr))
+					.map_err(Error)?
+			},
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Debug for Block {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Block")
+			.field("addr", &format_args!("{:#x}", self.addr))
+			.field("len", &self.len)
+			.field("kind", &self.kind)
+			.finish()
+	}
+}
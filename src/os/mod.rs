@@ -1,4 +1,8 @@
-use ::core::ffi::CStr;
+use ::core::{
+	ffi::CStr,
+	ops::Range,
+};
+use ::std::ffi::CString;
 
 #[cfg(unix)]
 pub mod unix;
@@ -14,6 +18,10 @@ pub(crate) use windows as imp;
 #[cfg(not(any(unix, windows)))]
 compile_error!("only `unix` and `windows` targets are supported");
 
+pub(crate) trait ErrorImpl {
+	fn kind(&self) -> crate::ErrorKind;
+}
+
 pub(crate) trait SegmentFlagsImpl: Sized + Copy {
 	fn has_x(&self) -> bool;
 	fn has_r(&self) -> bool;
@@ -22,9 +30,14 @@ pub(crate) trait SegmentFlagsImpl: Sized + Copy {
 }
 
 pub(crate) trait SegmentImpl {
+	fn kind(&self) -> crate::SegmentKind;
 	fn flags(&self) -> imp::SegmentFlags;
+	fn base_addr(&self) -> usize;
+	fn file_offset(&self) -> usize;
+	fn file_size(&self) -> usize;
 	fn virtual_addr(&self) -> usize;
 	fn size(&self) -> usize;
+	fn align(&self) -> usize;
 }
 
 pub(crate) trait LibraryImpl {
@@ -36,9 +49,19 @@ pub(crate) trait ObjectImpl {
 	fn is_main_program(&self) -> bool;
 	fn base_addr(&self) -> usize;
 	fn segments(&self) -> imp::Segments<'_>;
-	fn symbols(&self) -> imp::Symbols;
+	fn symbols(&self) -> Result<imp::Symbols, imp::Error>;
 	fn symbol(&self, symbols: &imp::Symbols, name: &CStr) -> *mut ();
-	fn library(&self, symbols: imp::Symbols) -> imp::Library;
+	fn lookup_symbol(&self, name: &CStr) -> *mut ();
+	fn function_containing(&self, addr: usize) -> Option<Range<usize>>;
+	fn functions(&self) -> imp::Functions<'_>;
+	fn library(&self, symbols: imp::Symbols) -> Result<imp::Library, imp::Error>;
+}
+
+pub(crate) trait OwnedObjectImpl: Sized + Clone + Send + Sync {
+	fn new(name: &CStr, object: &imp::Object<'_>) -> Self;
+	fn name(&self) -> &CStr;
+	fn object(&self) -> imp::Object<'_>;
+	fn segments(&self) -> imp::Segments<'_>;
 }
 
 pub(crate) trait ObjectsImpl
@@ -47,7 +70,10 @@ where
 	for<'a> imp::ModuleName<'a>: ModuleNameImpl,
 	for<'a> imp::Segments<'a>: Iterator<Item = imp::Segment<'a>>,
 	for<'a> imp::Segment<'a>: SegmentImpl,
+	for<'a> imp::Functions<'a>: Iterator<Item = Range<usize>>,
 	imp::Library: LibraryImpl,
+	imp::OwnedObject: OwnedObjectImpl,
+	imp::Error: ErrorImpl,
 	imp::SegmentFlags: SegmentFlagsImpl,
 {
 	fn init() -> Self;
@@ -57,6 +83,8 @@ where
 	fn fill_map<'a, M: ?Sized + crate::map::ObjectMap<'a>>(&self, map: &mut M) -> Result<(), imp::Error>;
 	fn map_by_name<R, F: FnOnce(imp::Object<'_>) -> R>(&self, name: &CStr, f: F) -> Result<Option<R>, imp::Error>;
 	fn for_each<F: FnMut(imp::ModuleName<'_>, imp::Object<'_>) -> bool>(&self, f: F) -> Result<(), imp::Error>;
+	fn nearest_symbol(&self, addr: usize) -> Option<(CString, usize)>;
+	fn generation(&self) -> Option<(u64, u64)>;
 }
 
 pub(crate) trait ModuleNameImpl {
@@ -0,0 +1,375 @@
+use ::core::{
+	ffi::{
+		CStr, c_char,
+	},
+	fmt,
+	ops::Range,
+	slice::from_raw_parts,
+};
+
+use crate::SegmentKind;
+
+use super::{
+	Segments, UnixObject,
+};
+
+macro_rules! dynamic_tags {
+	{
+		$(
+			$(#[$attr:meta])*
+			$name:ident = $const_name:ident = $value:literal,
+		)*
+	} => {
+		$(
+			$(#[$attr])*
+			pub const $const_name: isize = $value;
+		)*
+
+		/// Tag of an entry in the dynamic section.
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+		#[non_exhaustive]
+		pub enum DynamicTag {
+			$(
+				$(#[$attr])*
+				$name,
+			)*
+			/// Any other tag.
+			Other(isize),
+		}
+
+		impl DynamicTag {
+			pub const fn from_raw(raw: isize) -> Self {
+				match raw {
+					$($const_name => Self::$name,)*
+					other => Self::Other(other),
+				}
+			}
+
+			pub const fn into_raw(self) -> isize {
+				match self {
+					$(Self::$name => $const_name,)*
+					Self::Other(other) => other,
+				}
+			}
+		}
+	};
+}
+
+dynamic_tags! {
+	/// End of the dynamic section.
+	Null = DT_NULL = 0,
+	/// String table offset of the name of a needed library.
+	Needed = DT_NEEDED = 1,
+	/// Size of the PLT relocations, in bytes.
+	PltRelSize = DT_PLTRELSZ = 2,
+	/// Address of the PLT or GOT.
+	PltGot = DT_PLTGOT = 3,
+	/// Address of the SysV symbol hash table.
+	Hash = DT_HASH = 4,
+	/// Address of the string table.
+	StrTab = DT_STRTAB = 5,
+	/// Address of the symbol table.
+	SymTab = DT_SYMTAB = 6,
+	/// Address of the relocations with addends.
+	Rela = DT_RELA = 7,
+	/// Size of the relocations with addends, in bytes.
+	RelaSize = DT_RELASZ = 8,
+	/// Size of a single relocation with an addend, in bytes.
+	RelaEnt = DT_RELAENT = 9,
+	/// Size of the string table, in bytes.
+	StrSize = DT_STRSZ = 10,
+	/// Size of a single symbol table entry, in bytes.
+	SymEnt = DT_SYMENT = 11,
+	/// Address of the initialization function.
+	Init = DT_INIT = 12,
+	/// Address of the termination function.
+	Fini = DT_FINI = 13,
+	/// String table offset of the shared object name.
+	SoName = DT_SONAME = 14,
+	/// String table offset of the library search path.
+	RPath = DT_RPATH = 15,
+	/// Symbol resolution starts with the object itself.
+	Symbolic = DT_SYMBOLIC = 16,
+	/// Address of the relocations without addends.
+	Rel = DT_REL = 17,
+	/// Size of the relocations without addends, in bytes.
+	RelSize = DT_RELSZ = 18,
+	/// Size of a single relocation without an addend, in bytes.
+	RelEnt = DT_RELENT = 19,
+	/// Kind of relocations used for the PLT.
+	PltRel = DT_PLTREL = 20,
+	/// Reserved for debuggers.
+	Debug = DT_DEBUG = 21,
+	/// Relocations may modify non-writable segments.
+	TextRel = DT_TEXTREL = 22,
+	/// Address of the PLT relocations.
+	JmpRel = DT_JMPREL = 23,
+	/// All relocations must be processed before execution.
+	BindNow = DT_BIND_NOW = 24,
+	/// Address of the array of initialization functions.
+	InitArray = DT_INIT_ARRAY = 25,
+	/// Address of the array of termination functions.
+	FiniArray = DT_FINI_ARRAY = 26,
+	/// Size of the array of initialization functions, in bytes.
+	InitArraySize = DT_INIT_ARRAYSZ = 27,
+	/// Size of the array of termination functions, in bytes.
+	FiniArraySize = DT_FINI_ARRAYSZ = 28,
+	/// String table offset of the library search path.
+	RunPath = DT_RUNPATH = 29,
+	/// Flags of the object.
+	Flags = DT_FLAGS = 30,
+	/// Address of the array of pre-initialization functions.
+	PreinitArray = DT_PREINIT_ARRAY = 32,
+	/// Size of the array of pre-initialization functions, in bytes.
+	PreinitArraySize = DT_PREINIT_ARRAYSZ = 33,
+	/// Address of the GNU symbol hash table.
+	GnuHash = DT_GNU_HASH = 0x6ffffef5,
+	/// Number of relative relocations with addends.
+	RelaCount = DT_RELACOUNT = 0x6ffffff9,
+	/// Number of relative relocations without addends.
+	RelCount = DT_RELCOUNT = 0x6ffffffa,
+	/// Additional flags of the object.
+	Flags1 = DT_FLAGS_1 = 0x6ffffffb,
+	/// Address of the symbol version table.
+	VerSym = DT_VERSYM = 0x6ffffff0,
+	/// Address of the version definitions.
+	VerDef = DT_VERDEF = 0x6ffffffc,
+	/// Number of version definitions.
+	VerDefNum = DT_VERDEFNUM = 0x6ffffffd,
+	/// Address of the needed versions.
+	VerNeed = DT_VERNEED = 0x6ffffffe,
+	/// Number of needed versions.
+	VerNeedNum = DT_VERNEEDNUM = 0x6fffffff,
+}
+
+/// `DT_FLAGS` bit: the object may use `$ORIGIN`.
+pub const DF_ORIGIN: usize = 0x1;
+/// `DT_FLAGS` bit: symbol resolution starts with the object itself.
+pub const DF_SYMBOLIC: usize = 0x2;
+/// `DT_FLAGS` bit: relocations may modify non-writable segments.
+pub const DF_TEXTREL: usize = 0x4;
+/// `DT_FLAGS` bit: all relocations must be processed before execution.
+pub const DF_BIND_NOW: usize = 0x8;
+/// `DT_FLAGS` bit: the object uses the static TLS model.
+pub const DF_STATIC_TLS: usize = 0x10;
+
+/// `DT_FLAGS_1` bit: all relocations must be processed before execution.
+pub const DF_1_NOW: usize = 0x1;
+/// `DT_FLAGS_1` bit: the object's symbols are made available globally.
+pub const DF_1_GLOBAL: usize = 0x2;
+/// `DT_FLAGS_1` bit: the object cannot be unloaded.
+pub const DF_1_NODELETE: usize = 0x8;
+/// `DT_FLAGS_1` bit: the object is initialized before any other object.
+pub const DF_1_INITFIRST: usize = 0x20;
+/// `DT_FLAGS_1` bit: the object cannot be loaded with `dlopen`.
+pub const DF_1_NOOPEN: usize = 0x40;
+/// `DT_FLAGS_1` bit: the object may use `$ORIGIN`.
+pub const DF_1_ORIGIN: usize = 0x80;
+/// `DT_FLAGS_1` bit: the object is a position-independent executable.
+pub const DF_1_PIE: usize = 0x8000000;
+
+/// Single entry of the dynamic section, equivalent to `ElfW(Dyn)`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct DynamicEntry {
+	pub tag: isize,
+	pub value: usize,
+}
+
+impl DynamicEntry {
+	pub const fn tag(&self) -> DynamicTag {
+		DynamicTag::from_raw(self.tag)
+	}
+}
+
+impl fmt::Debug for DynamicEntry {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("DynamicEntry")
+			.field("tag", &self.tag())
+			.field("value", &format_args!("{:#x}", self.value))
+			.finish()
+	}
+}
+
+impl UnixObject {
+	/// Returns the dynamic section of the object, if it has one.
+	pub fn dynamic(&self) -> Option<DynamicSection<'_>> {
+		let segment = Segments::new(self).find(move |segment| segment.header.kind() == SegmentKind::Dynamic)?;
+		let entries = unsafe {
+			from_raw_parts(
+				segment.base_addr.wrapping_add(segment.header.virtual_addr()) as *const DynamicEntry,
+				segment.header.size() / size_of::<DynamicEntry>(),
+			)
+		};
+		let len = entries.iter().position(move |entry| entry.tag == DT_NULL).unwrap_or(entries.len());
+		Some(DynamicSection {
+			entries: &entries[..len],
+			base_addr: self.base_addr(),
+			load_range: self.load_range(),
+		})
+	}
+}
+
+/// View of the dynamic section (`PT_DYNAMIC`) of a loaded object.
+#[derive(Debug, Clone)]
+pub struct DynamicSection<'a> {
+	entries: &'a [DynamicEntry],
+	base_addr: usize,
+	load_range: Range<usize>,
+}
+
+impl<'a> DynamicSection<'a> {
+	/// Returns all of the entries before `DT_NULL`.
+	pub const fn entries(&self) -> &'a [DynamicEntry] {
+		self.entries
+	}
+
+	/// Returns the base address of the object that the section belongs to.
+	pub const fn base_addr(&self) -> usize {
+		self.base_addr
+	}
+
+	/// Returns the value of the first entry with the given `tag`.
+	pub fn value(&self, tag: DynamicTag) -> Option<usize> {
+		let tag = tag.into_raw();
+		self.entries.iter().find(move |entry| entry.tag == tag).map(move |entry| entry.value)
+	}
+
+	/// Returns the value of the first entry with the given `tag` as an absolute address.
+	/// 
+	/// Some loaders (such as glibc's) relocate the `d_ptr` values in place,
+	/// while others leave them relative to the base address.
+	/// Both cases are handled by checking whether the value already lies within the object.
+	pub fn ptr(&self, tag: DynamicTag) -> Option<usize> {
+		self.value(tag).map(move |value| self.resolve_ptr(value))
+	}
+
+	/// Converts a `d_ptr` value of this section to an absolute address.
+	/// 
+	/// See [`ptr`](Self::ptr) for more information.
+	pub fn resolve_ptr(&self, value: usize) -> usize {
+		if self.load_range.contains(&value) {
+			value
+		} else {
+			self.base_addr.wrapping_add(value)
+		}
+	}
+
+	/// Returns the string at `offset` in the string table.
+	pub fn string(&self, offset: usize) -> Option<&'a CStr> {
+		let strtab = self.ptr(DynamicTag::StrTab)?;
+		if self.value(DynamicTag::StrSize).is_some_and(move |size| offset >= size) {
+			return None
+		}
+		unsafe { Some(CStr::from_ptr(strtab.wrapping_add(offset) as *const c_char)) }
+	}
+
+	fn string_of(&self, tag: DynamicTag) -> Option<&'a CStr> {
+		self.value(tag).and_then(move |offset| self.string(offset))
+	}
+
+	/// Returns the `DT_SONAME` of the object.
+	pub fn soname(&self) -> Option<&'a CStr> {
+		self.string_of(DynamicTag::SoName)
+	}
+
+	/// Returns an iterator over the names of the libraries that the object needs (`DT_NEEDED`).
+	pub fn needed(&self) -> Needed<'a> {
+		Needed {
+			section: self.clone(),
+			entries: self.entries.iter(),
+		}
+	}
+
+	/// Returns the deprecated `DT_RPATH` of the object.
+	pub fn rpath(&self) -> Option<&'a CStr> {
+		self.string_of(DynamicTag::RPath)
+	}
+
+	/// Returns the `DT_RUNPATH` of the object.
+	pub fn runpath(&self) -> Option<&'a CStr> {
+		self.string_of(DynamicTag::RunPath)
+	}
+
+	/// Returns the `DT_FLAGS` of the object, such as [`DF_BIND_NOW`].
+	pub fn flags(&self) -> Option<usize> {
+		self.value(DynamicTag::Flags)
+	}
+
+	/// Returns the `DT_FLAGS_1` of the object, such as [`DF_1_PIE`].
+	pub fn flags_1(&self) -> Option<usize> {
+		self.value(DynamicTag::Flags1)
+	}
+
+	/// Returns the address of the initialization function (`DT_INIT`).
+	pub fn init(&self) -> Option<usize> {
+		self.ptr(DynamicTag::Init)
+	}
+
+	/// Returns the address of the termination function (`DT_FINI`).
+	pub fn fini(&self) -> Option<usize> {
+		self.ptr(DynamicTag::Fini)
+	}
+
+	fn array(&self, addr: DynamicTag, size: DynamicTag) -> Option<&'a [usize]> {
+		let addr = self.ptr(addr)?;
+		let size = self.value(size)?;
+		unsafe { Some(from_raw_parts(addr as *const usize, size / size_of::<usize>())) }
+	}
+
+	/// Returns the array of pre-initialization functions (`DT_PREINIT_ARRAY`).
+	pub fn preinit_array(&self) -> Option<&'a [usize]> {
+		self.array(DynamicTag::PreinitArray, DynamicTag::PreinitArraySize)
+	}
+
+	/// Returns the array of initialization functions (`DT_INIT_ARRAY`).
+	pub fn init_array(&self) -> Option<&'a [usize]> {
+		self.array(DynamicTag::InitArray, DynamicTag::InitArraySize)
+	}
+
+	/// Returns the array of termination functions (`DT_FINI_ARRAY`).
+	pub fn fini_array(&self) -> Option<&'a [usize]> {
+		self.array(DynamicTag::FiniArray, DynamicTag::FiniArraySize)
+	}
+
+	/// Returns the address of the symbol table (`DT_SYMTAB`).
+	pub fn symtab(&self) -> Option<usize> {
+		self.ptr(DynamicTag::SymTab)
+	}
+
+	/// Returns the address of the string table (`DT_STRTAB`).
+	pub fn strtab(&self) -> Option<usize> {
+		self.ptr(DynamicTag::StrTab)
+	}
+
+	/// Returns the address of the SysV symbol hash table (`DT_HASH`).
+	pub fn hash(&self) -> Option<usize> {
+		self.ptr(DynamicTag::Hash)
+	}
+
+	/// Returns the address of the GNU symbol hash table (`DT_GNU_HASH`).
+	pub fn gnu_hash(&self) -> Option<usize> {
+		self.ptr(DynamicTag::GnuHash)
+	}
+}
+
+/// Iterator over the `DT_NEEDED` entries of a dynamic section.
+/// 
+/// See [`DynamicSection::needed`].
+#[derive(Debug, Clone)]
+pub struct Needed<'a> {
+	section: DynamicSection<'a>,
+	entries: ::core::slice::Iter<'a, DynamicEntry>,
+}
+
+impl<'a> Iterator for Needed<'a> {
+	type Item = &'a CStr;
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let entry = self.entries.next()?;
+			if entry.tag == DT_NEEDED && let Some(name) = self.section.string(entry.value) {
+				return Some(name)
+			}
+		}
+	}
+}
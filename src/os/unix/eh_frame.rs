@@ -0,0 +1,421 @@
+use ::core::{
+	cmp::Ordering,
+	ffi::CStr,
+	marker::PhantomData,
+	ops::Range,
+};
+
+use super::UnixObject;
+use crate::SegmentKind;
+
+/// Absolute pointer of the native size.
+pub const DW_EH_PE_ABSPTR: u8 = 0x00;
+pub const DW_EH_PE_ULEB128: u8 = 0x01;
+pub const DW_EH_PE_UDATA2: u8 = 0x02;
+pub const DW_EH_PE_UDATA4: u8 = 0x03;
+pub const DW_EH_PE_UDATA8: u8 = 0x04;
+pub const DW_EH_PE_SLEB128: u8 = 0x09;
+pub const DW_EH_PE_SDATA2: u8 = 0x0a;
+pub const DW_EH_PE_SDATA4: u8 = 0x0b;
+pub const DW_EH_PE_SDATA8: u8 = 0x0c;
+/// Value is relative to the address of the value itself.
+pub const DW_EH_PE_PCREL: u8 = 0x10;
+/// Value is relative to the start of `.eh_frame_hdr`.
+pub const DW_EH_PE_DATAREL: u8 = 0x30;
+/// Value is the address of the actual value.
+pub const DW_EH_PE_INDIRECT: u8 = 0x80;
+/// Value is not present.
+pub const DW_EH_PE_OMIT: u8 = 0xff;
+
+/// Reads a value in the pointer encoding `encoding` at `*ptr`, advancing `*ptr` past it.
+/// 
+/// `data_base` is the address that [`DW_EH_PE_DATAREL`] values are relative to.
+/// 
+/// # Safety
+/// `*ptr` must point to a valid encoded value.
+pub(crate) unsafe fn read_encoded(ptr: &mut usize, encoding: u8, data_base: usize) -> Option<usize> {
+	if encoding == DW_EH_PE_OMIT {
+		return None
+	}
+	let start = *ptr;
+	unsafe {
+		let value = match encoding & 0x0f {
+			DW_EH_PE_ABSPTR => read_raw::<usize>(ptr),
+			DW_EH_PE_ULEB128 => read_uleb128(ptr) as usize,
+			DW_EH_PE_UDATA2 => read_raw::<u16>(ptr) as usize,
+			DW_EH_PE_UDATA4 => read_raw::<u32>(ptr) as usize,
+			DW_EH_PE_UDATA8 => read_raw::<u64>(ptr) as usize,
+			DW_EH_PE_SLEB128 => read_sleb128(ptr) as usize,
+			DW_EH_PE_SDATA2 => read_raw::<i16>(ptr) as usize,
+			DW_EH_PE_SDATA4 => read_raw::<i32>(ptr) as usize,
+			DW_EH_PE_SDATA8 => read_raw::<i64>(ptr) as usize,
+			_ => return None,
+		};
+		let value = match encoding & 0x70 {
+			0 => value,
+			DW_EH_PE_PCREL => start.wrapping_add(value),
+			DW_EH_PE_DATAREL => data_base.wrapping_add(value),
+			_ => return None,
+		};
+		if encoding & DW_EH_PE_INDIRECT != 0 {
+			Some(*(value as *const usize))
+		} else {
+			Some(value)
+		}
+	}
+}
+
+/// Returns the size of values in the pointer encoding `encoding`,
+/// or `None` if they aren't of a fixed size.
+pub(crate) const fn encoded_size(encoding: u8) -> Option<usize> {
+	match encoding & 0x0f {
+		DW_EH_PE_ABSPTR => Some(size_of::<usize>()),
+		DW_EH_PE_UDATA2 | DW_EH_PE_SDATA2 => Some(2),
+		DW_EH_PE_UDATA4 | DW_EH_PE_SDATA4 => Some(4),
+		DW_EH_PE_UDATA8 | DW_EH_PE_SDATA8 => Some(8),
+		_ => None,
+	}
+}
+
+unsafe fn read_raw<T: Copy>(ptr: &mut usize) -> T {
+	unsafe {
+		let value = (*ptr as *const T).read_unaligned();
+		*ptr += size_of::<T>();
+		value
+	}
+}
+
+pub(crate) unsafe fn read_uleb128(ptr: &mut usize) -> u64 {
+	let mut result = 0;
+	let mut shift = 0;
+	loop {
+		let byte = unsafe { read_raw::<u8>(ptr) };
+		if shift < 64 {
+			result |= ((byte & 0x7f) as u64) << shift;
+		}
+		shift += 7;
+		if byte & 0x80 == 0 {
+			return result
+		}
+	}
+}
+
+pub(crate) unsafe fn read_sleb128(ptr: &mut usize) -> i64 {
+	let mut result = 0;
+	let mut shift = 0;
+	loop {
+		let byte = unsafe { read_raw::<u8>(ptr) };
+		if shift < 64 {
+			result |= ((byte & 0x7f) as i64) << shift;
+		}
+		shift += 7;
+		if byte & 0x80 == 0 {
+			if shift < 64 && byte & 0x40 != 0 {
+				result |= -1 << shift;
+			}
+			return result
+		}
+	}
+}
+
+impl UnixObject {
+	/// Returns the `.eh_frame_hdr` section of the object, as located by its `PT_GNU_EH_FRAME` segment.
+	pub fn eh_frame_hdr(&self) -> Option<EhFrameHdr<'_>> {
+		let header = self.headers().iter().find(move |header| header.kind() == SegmentKind::GnuEhFrame)?;
+		unsafe { EhFrameHdr::from_addr(self.base_addr().wrapping_add(header.virtual_addr())) }
+	}
+
+	/// Returns the range of addresses of the function that contains `addr`,
+	/// according to the unwind information of the object.
+	pub fn function_containing(&self, addr: usize) -> Option<Range<usize>> {
+		self.eh_frame_hdr()?.function_containing(addr)
+	}
+
+	/// Returns an iterator over the ranges of addresses of all functions
+	/// that have unwind information in the object.
+	pub fn functions(&self) -> Functions<'_> {
+		match self.eh_frame_hdr() {
+			Some(hdr) => hdr.functions(),
+			None => Functions::empty(),
+		}
+	}
+}
+
+/// View of the `.eh_frame_hdr` section of a loaded object.
+/// 
+/// The section has the address of `.eh_frame`,
+/// and usually a table of the start addresses of all functions with unwind information,
+/// sorted so that it can be binary searched.
+#[derive(Debug, Clone, Copy)]
+pub struct EhFrameHdr<'a> {
+	addr: usize,
+	eh_frame: Option<usize>,
+	table: usize,
+	table_encoding: u8,
+	entry_size: usize,
+	len: usize,
+	_data: PhantomData<&'a [u8]>,
+}
+
+impl<'a> EhFrameHdr<'a> {
+	/// # Safety
+	/// `addr` must point to a valid `.eh_frame_hdr` section.
+	pub unsafe fn from_addr(addr: usize) -> Option<Self> {
+		unsafe {
+			let header = addr as *const u8;
+			if *header != 1 {
+				return None
+			}
+			let eh_frame_encoding = *header.add(1);
+			let count_encoding = *header.add(2);
+			let table_encoding = *header.add(3);
+			let mut ptr = addr + 4;
+			let eh_frame = read_encoded(&mut ptr, eh_frame_encoding, addr);
+			let len = read_encoded(&mut ptr, count_encoding, addr);
+			// The table can only be searched if its entries are of a fixed size.
+			let (len, entry_size) = match (len, encoded_size(table_encoding)) {
+				(Some(len), Some(size)) if table_encoding != DW_EH_PE_OMIT => (len, size * 2),
+				_ => (0, 0),
+			};
+			Some(Self {
+				addr,
+				eh_frame,
+				table: ptr,
+				table_encoding,
+				entry_size,
+				len,
+				_data: PhantomData,
+			})
+		}
+	}
+
+	/// Returns the address of the section.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	/// Returns the address of the `.eh_frame` section.
+	pub const fn eh_frame_addr(&self) -> Option<usize> {
+		self.eh_frame
+	}
+
+	/// Returns the number of entries in the search table.
+	pub const fn len(&self) -> usize {
+		self.len
+	}
+
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns the entry at `index` in the search table.
+	pub fn get(&self, index: usize) -> Option<SearchTableEntry> {
+		if index >= self.len {
+			return None
+		}
+		unsafe {
+			let mut ptr = self.table + index * self.entry_size;
+			Some(SearchTableEntry {
+				initial_location: read_encoded(&mut ptr, self.table_encoding, self.addr)?,
+				fde_addr: read_encoded(&mut ptr, self.table_encoding, self.addr)?,
+			})
+		}
+	}
+
+	/// Returns an iterator over the entries of the search table.
+	pub fn entries(&self) -> SearchTableEntries<'a> {
+		SearchTableEntries {
+			hdr: *self,
+			index: 0,
+		}
+	}
+
+	/// Returns the range of addresses of the function that is described by the FDE of `entry`.
+	pub fn function_range(&self, entry: &SearchTableEntry) -> Option<Range<usize>> {
+		// SAFETY: The search table only has addresses of FDEs in `.eh_frame`.
+		unsafe { fde_pc_range(entry.fde_addr) }
+	}
+
+	/// Returns the range of addresses of the function that contains `addr`.
+	pub fn function_containing(&self, addr: usize) -> Option<Range<usize>> {
+		let range = self.function_range(&self.lookup(addr)?)?;
+		range.contains(&addr).then_some(range)
+	}
+
+	/// Returns an iterator over the ranges of addresses of all functions in the search table,
+	/// in order of their start addresses.
+	pub fn functions(&self) -> Functions<'a> {
+		Functions {
+			entries: self.entries(),
+		}
+	}
+
+	/// Returns the entry with the greatest initial location that is not greater than `addr`.
+	/// 
+	/// Note that `addr` may still be past the end of the function of the entry.
+	pub fn lookup(&self, addr: usize) -> Option<SearchTableEntry> {
+		let mut low = 0;
+		let mut high = self.len;
+		while low < high {
+			let middle = low + (high - low) / 2;
+			match self.get(middle)?.initial_location.cmp(&addr) {
+				Ordering::Greater => high = middle,
+				_ => low = middle + 1,
+			}
+		}
+		self.get(low.checked_sub(1)?)
+	}
+}
+
+/// Entry of the search table of an [`EhFrameHdr`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SearchTableEntry {
+	/// Absolute address of the start of the function.
+	pub initial_location: usize,
+	/// Absolute address of the function's frame description entry in `.eh_frame`.
+	pub fde_addr: usize,
+}
+
+/// Iterator over the entries of the search table of an [`EhFrameHdr`].
+#[derive(Debug, Clone)]
+pub struct SearchTableEntries<'a> {
+	hdr: EhFrameHdr<'a>,
+	index: usize,
+}
+
+impl Iterator for SearchTableEntries<'_> {
+	type Item = SearchTableEntry;
+	fn next(&mut self) -> Option<Self::Item> {
+		let entry = self.hdr.get(self.index)?;
+		self.index += 1;
+		Some(entry)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = self.hdr.len.saturating_sub(self.index);
+		(0, Some(remaining))
+	}
+}
+
+/// Iterator over the ranges of addresses of the functions in an [`EhFrameHdr`].
+/// 
+/// See [`EhFrameHdr::functions`].
+#[derive(Debug, Clone)]
+pub struct Functions<'a> {
+	entries: SearchTableEntries<'a>,
+}
+
+impl Functions<'_> {
+	/// Returns an iterator that doesn't yield any functions,
+	/// for objects without an `.eh_frame_hdr`.
+	pub(crate) const fn empty() -> Self {
+		Self {
+			entries: SearchTableEntries {
+				hdr: EhFrameHdr {
+					addr: 0,
+					eh_frame: None,
+					table: 0,
+					table_encoding: DW_EH_PE_OMIT,
+					entry_size: 0,
+					len: 0,
+					_data: PhantomData,
+				},
+				index: 0,
+			},
+		}
+	}
+}
+
+impl Iterator for Functions<'_> {
+	type Item = Range<usize>;
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let entry = self.entries.next()?;
+			if let Some(range) = self.entries.hdr.function_range(&entry) {
+				return Some(range)
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.entries.size_hint()
+	}
+}
+
+/// Reads the length of a CIE or FDE at `*ptr`, advancing `*ptr` past it,
+/// and returns the address of the end of the entry.
+unsafe fn read_entry_length(ptr: &mut usize) -> Option<usize> {
+	unsafe {
+		let length = match read_raw::<u32>(ptr) {
+			0 => return None,
+			0xffff_ffff => read_raw::<u64>(ptr) as usize,
+			length => length as usize,
+		};
+		Some(ptr.wrapping_add(length))
+	}
+}
+
+/// Returns the pointer encoding of the addresses in the FDEs of the CIE at `addr`.
+unsafe fn cie_fde_encoding(addr: usize) -> Option<u8> {
+	unsafe {
+		let mut ptr = addr;
+		read_entry_length(&mut ptr)?;
+		if read_raw::<u32>(&mut ptr) != 0 {
+			return None
+		}
+		let version = read_raw::<u8>(&mut ptr);
+		let augmentation = CStr::from_ptr(ptr as *const _).to_bytes();
+		ptr += augmentation.len() + 1;
+		if augmentation.starts_with(b"eh") {
+			ptr += size_of::<usize>();
+		}
+		// Code alignment factor, data alignment factor and return address register.
+		read_uleb128(&mut ptr);
+		read_sleb128(&mut ptr);
+		if version == 1 {
+			ptr += 1;
+		} else {
+			read_uleb128(&mut ptr);
+		}
+
+		let Some((b'z', augmentation)) = augmentation.split_first() else {
+			return Some(DW_EH_PE_ABSPTR)
+		};
+		read_uleb128(&mut ptr);
+		for &c in augmentation {
+			match c {
+				b'R' => return Some(read_raw::<u8>(&mut ptr)),
+				b'L' => ptr += 1,
+				b'P' => {
+					// The personality routine is skipped without following indirect pointers.
+					let encoding = read_raw::<u8>(&mut ptr);
+					read_encoded(&mut ptr, encoding & !DW_EH_PE_INDIRECT, 0)?;
+				}
+				b'S' | b'B' => {}
+				_ => return None,
+			}
+		}
+		Some(DW_EH_PE_ABSPTR)
+	}
+}
+
+/// Returns the range of addresses that is described by the FDE at `addr`.
+/// 
+/// # Safety
+/// `addr` must point to a valid FDE in `.eh_frame`.
+pub unsafe fn fde_pc_range(addr: usize) -> Option<Range<usize>> {
+	unsafe {
+		let mut ptr = addr;
+		read_entry_length(&mut ptr)?;
+		let cie_pointer_addr = ptr;
+		let cie_pointer = read_raw::<u32>(&mut ptr) as usize;
+		if cie_pointer == 0 {
+			// This is a CIE.
+			return None
+		}
+		let encoding = cie_fde_encoding(cie_pointer_addr.wrapping_sub(cie_pointer))?;
+		let start = read_encoded(&mut ptr, encoding, 0)?;
+		let len = read_encoded(&mut ptr, encoding & 0x0f, 0)?;
+		Some(start..start.wrapping_add(len))
+	}
+}
@@ -0,0 +1,117 @@
+use ::core::{
+	ffi::CStr,
+	fmt::{
+		self, Write,
+	},
+};
+use ::libc::dlerror;
+use ::std::{
+	error::Error as StdError,
+	ffi::CString,
+	io,
+};
+
+use crate::ErrorKind;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+	/// The requested object or symbol doesn't exist.
+	NotFound,
+	/// The dynamic loader reported an error.
+	Loader(LoaderError),
+	/// An operating system call failed.
+	Os(io::Error),
+	/// The object's in-memory image doesn't have the expected structure.
+	Malformed(&'static str),
+	/// The operation can't be performed on the given code or data.
+	Unsupported(&'static str),
+}
+impl Error {
+	/// Returns an [`Os`](Error::Os) error with the value of `errno`.
+	pub fn last_os_error() -> Self {
+		Self::Os(io::Error::last_os_error())
+	}
+}
+impl super::super::ErrorImpl for Error {
+	fn kind(&self) -> ErrorKind {
+		match self {
+			Self::NotFound => ErrorKind::NotFound,
+			Self::Loader(..) => ErrorKind::Loader,
+			Self::Os(..) => ErrorKind::Os,
+			Self::Malformed(..) => ErrorKind::Malformed,
+			Self::Unsupported(..) => ErrorKind::Unsupported,
+		}
+	}
+}
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::NotFound => f.write_str("not found"),
+			Self::Loader(error) => fmt::Display::fmt(error, f),
+			Self::Os(error) => fmt::Display::fmt(error, f),
+			Self::Malformed(what) => write!(f, "malformed object: {what}"),
+			Self::Unsupported(what) => write!(f, "unsupported: {what}"),
+		}
+	}
+}
+impl StdError for Error {
+	fn source(&self) -> Option<&(dyn StdError + 'static)> {
+		match self {
+			Self::Loader(error) => Some(error),
+			Self::Os(error) => Some(error),
+			_ => None,
+		}
+	}
+}
+impl From<LoaderError> for Error {
+	fn from(error: LoaderError) -> Self {
+		Self::Loader(error)
+	}
+}
+impl From<io::Error> for Error {
+	fn from(error: io::Error) -> Self {
+		Self::Os(error)
+	}
+}
+
+/// Owned copy of a message reported by `dlerror`.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct LoaderError(CString);
+impl LoaderError {
+	/// Takes the last error message of the loader on the current thread, if there is one.
+	/// 
+	/// The message is copied immediately,
+	/// since the buffer returned by `dlerror` may be reused by later loader calls.
+	pub fn take() -> Option<Self> {
+		unsafe {
+			let message_ptr = dlerror();
+			if !message_ptr.is_null() {
+				Some(Self::from_c_str(CStr::from_ptr(message_ptr)))
+			} else {
+				None
+			}
+		}
+	}
+
+	fn from_c_str(s: &CStr) -> Self {
+		Self(CString::from(s))
+	}
+
+	pub fn message(&self) -> &CStr {
+		&self.0
+	}
+}
+impl fmt::Display for LoaderError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for chunk in self.0.as_bytes().utf8_chunks() {
+			f.write_str(chunk.valid())?;
+			for _ in chunk.invalid() {
+				f.write_char(char::REPLACEMENT_CHARACTER)?;
+			}
+		}
+		Ok(())
+	}
+}
+impl StdError for LoaderError {}
@@ -0,0 +1,257 @@
+use ::core::{
+	ffi::CStr,
+	slice::from_raw_parts,
+};
+
+use super::{
+	DynamicSection, DynamicSymbol, UnixObject,
+};
+
+/// Bit of `DT_VERSYM` entries that marks the symbol as a non-default version.
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+
+/// SysV symbol hash table (`DT_HASH`).
+#[derive(Debug, Clone, Copy)]
+pub struct SysvHashTable<'a> {
+	buckets: &'a [u32],
+	chains: &'a [u32],
+}
+
+impl<'a> SysvHashTable<'a> {
+	/// # Safety
+	/// `addr` must point to a valid SysV hash table.
+	pub unsafe fn from_addr(addr: usize) -> Self {
+		unsafe {
+			let header = addr as *const u32;
+			let n_buckets = *header as usize;
+			let n_chains = *header.add(1) as usize;
+			let buckets = header.add(2);
+			Self {
+				buckets: from_raw_parts(buckets, n_buckets),
+				chains: from_raw_parts(buckets.add(n_buckets), n_chains),
+			}
+		}
+	}
+
+	/// Returns the number of symbols in the symbol table, which is the same as the number of chains.
+	pub const fn symbol_count(&self) -> usize {
+		self.chains.len()
+	}
+
+	/// Returns the index of the first symbol named `name` for which `matches` returns `true`.
+	/// 
+	/// `matches` should compare the name of the symbol at the given index with `name`.
+	pub fn find<F: FnMut(usize) -> bool>(&self, name: &[u8], mut matches: F) -> Option<usize> {
+		if self.buckets.is_empty() {
+			return None
+		}
+		let hash = sysv_hash(name);
+		let mut index = *self.buckets.get(hash as usize % self.buckets.len())? as usize;
+		while index != 0 {
+			if matches(index) {
+				return Some(index)
+			}
+			index = *self.chains.get(index)? as usize;
+		}
+		None
+	}
+}
+
+/// GNU symbol hash table (`DT_GNU_HASH`).
+#[derive(Debug, Clone, Copy)]
+pub struct GnuHashTable<'a> {
+	symbol_offset: u32,
+	bloom_shift: u32,
+	bloom: &'a [usize],
+	buckets: &'a [u32],
+	chains: *const u32,
+}
+
+impl<'a> GnuHashTable<'a> {
+	/// # Safety
+	/// `addr` must point to a valid GNU hash table.
+	pub unsafe fn from_addr(addr: usize) -> Self {
+		unsafe {
+			let header = addr as *const u32;
+			let n_buckets = *header as usize;
+			let symbol_offset = *header.add(1);
+			let bloom_size = *header.add(2) as usize;
+			let bloom_shift = *header.add(3);
+			let bloom = header.add(4) as *const usize;
+			let buckets = bloom.add(bloom_size) as *const u32;
+			Self {
+				symbol_offset,
+				bloom_shift,
+				bloom: from_raw_parts(bloom, bloom_size),
+				buckets: from_raw_parts(buckets, n_buckets),
+				chains: buckets.add(n_buckets),
+			}
+		}
+	}
+
+	/// Returns the index of the first symbol that is accessible through the table.
+	pub const fn symbol_offset(&self) -> u32 {
+		self.symbol_offset
+	}
+
+	fn chain(&self, index: u32) -> u32 {
+		unsafe { *self.chains.add((index - self.symbol_offset) as usize) }
+	}
+
+	/// Returns the number of symbols in the symbol table.
+	/// 
+	/// The table doesn't store this directly,
+	/// so it is found by walking the chain of the last bucket to its end.
+	pub fn symbol_count(&self) -> usize {
+		let Some(mut index) = self.buckets.iter().copied().max().filter(move |&max| max >= self.symbol_offset) else {
+			return self.symbol_offset as usize
+		};
+		while self.chain(index) & 1 == 0 {
+			index += 1;
+		}
+		index as usize + 1
+	}
+
+	/// Returns the index of the first symbol named `name` for which `matches` returns `true`.
+	/// 
+	/// `matches` should compare the name of the symbol at the given index with `name`.
+	/// It is only called for symbols whose hash is the same as the one of `name`.
+	pub fn find<F: FnMut(usize) -> bool>(&self, name: &[u8], mut matches: F) -> Option<usize> {
+		if self.buckets.is_empty() || self.bloom.is_empty() {
+			return None
+		}
+		let hash = gnu_hash(name);
+
+		let word_bits = usize::BITS;
+		let word = self.bloom[(hash / word_bits) as usize % self.bloom.len()];
+		let mask = (1 << (hash % word_bits)) | (1 << ((hash >> self.bloom_shift) % word_bits));
+		if word & mask != mask {
+			return None
+		}
+
+		let mut index = self.buckets[hash as usize % self.buckets.len()];
+		if index < self.symbol_offset {
+			return None
+		}
+		loop {
+			let chain_hash = self.chain(index);
+			if (chain_hash | 1) == (hash | 1) && matches(index as usize) {
+				return Some(index as usize)
+			}
+			if chain_hash & 1 != 0 {
+				return None
+			}
+			index += 1;
+		}
+	}
+}
+
+/// Symbol hash table of an object, preferring the GNU one if it's available.
+#[derive(Debug, Clone, Copy)]
+pub enum HashTable<'a> {
+	Gnu(GnuHashTable<'a>),
+	Sysv(SysvHashTable<'a>),
+}
+
+impl<'a> HashTable<'a> {
+	/// Returns the hash table that is referenced by `section`.
+	pub fn from_dynamic(section: &DynamicSection<'a>) -> Option<Self> {
+		unsafe {
+			if let Some(addr) = section.gnu_hash() {
+				Some(Self::Gnu(GnuHashTable::from_addr(addr)))
+			} else {
+				section.hash().map(move |addr| Self::Sysv(SysvHashTable::from_addr(addr)))
+			}
+		}
+	}
+
+	/// Returns the number of symbols in the symbol table.
+	pub fn symbol_count(&self) -> usize {
+		match self {
+			Self::Gnu(table) => table.symbol_count(),
+			Self::Sysv(table) => table.symbol_count(),
+		}
+	}
+
+	/// Returns the index of the first symbol named `name` for which `matches` returns `true`.
+	pub fn find<F: FnMut(usize) -> bool>(&self, name: &[u8], matches: F) -> Option<usize> {
+		match self {
+			Self::Gnu(table) => table.find(name, matches),
+			Self::Sysv(table) => table.find(name, matches),
+		}
+	}
+}
+
+/// Hash function used by [`GnuHashTable`].
+pub const fn gnu_hash(name: &[u8]) -> u32 {
+	let mut hash: u32 = 5381;
+	let mut i = 0;
+	while i < name.len() {
+		hash = hash.wrapping_mul(33).wrapping_add(name[i] as u32);
+		i += 1;
+	}
+	hash
+}
+
+/// Hash function used by [`SysvHashTable`].
+pub const fn sysv_hash(name: &[u8]) -> u32 {
+	let mut hash: u32 = 0;
+	let mut i = 0;
+	while i < name.len() {
+		hash = (hash << 4).wrapping_add(name[i] as u32);
+		let high = hash & 0xf0000000;
+		if high != 0 {
+			hash ^= high >> 24;
+		}
+		hash &= !high;
+		i += 1;
+	}
+	hash
+}
+
+impl UnixObject {
+	/// Finds the exported symbol named `name` in the object's symbol hash table.
+	/// 
+	/// See [`DynamicSection::lookup`].
+	pub fn lookup_symbol(&self, name: &CStr) -> Option<DynamicSymbol<'_>> {
+		self.dynamic()?.lookup(name)
+	}
+
+	/// Finds the exported symbol named `name` with the given `version` in the object's symbol hash table.
+	/// 
+	/// See [`DynamicSection::lookup_versioned`].
+	pub fn lookup_symbol_versioned(&self, name: &CStr, version: &CStr) -> Option<DynamicSymbol<'_>> {
+		self.dynamic()?.lookup_versioned(name, version)
+	}
+}
+
+impl<'a> DynamicSection<'a> {
+	/// Finds the exported symbol named `name` in the symbol hash table of the section.
+	/// 
+	/// Unlike `dlsym`, this doesn't use the loader at all,
+	/// so it works for any object that has a hash table,
+	/// including the vDSO and objects in other link map namespaces.
+	/// If there are multiple versions of the symbol, the default one is returned.
+	/// 
+	/// Note that for [`GnuIfunc`](super::SymbolKind::GnuIfunc) symbols,
+	/// the address is that of the resolver function.
+	pub fn lookup(&self, name: &CStr) -> Option<DynamicSymbol<'a>> {
+		let table = HashTable::from_dynamic(self)?;
+		let name = name.to_bytes();
+		let mut found = None;
+		table.find(name, |index| {
+			// SAFETY: The hash table only yields indices of symbols in the table.
+			let Some(symbol) = (unsafe { self.symbol(index) }) else {
+				return false
+			};
+			let is_match = symbol.raw().is_exported()
+				&& symbol.name().to_bytes() == name
+				&& symbol.version_index().is_none_or(move |version| version & VERSYM_HIDDEN == 0);
+			if is_match {
+				found = Some(symbol);
+			}
+			is_match
+		});
+		found
+	}
+}
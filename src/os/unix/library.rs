@@ -1,20 +1,14 @@
+use ::core::ptr::null;
 use ::libc::{
 	c_void,
-	RTLD_LAZY, RTLD_NOLOAD,
+	RTLD_DEFAULT, RTLD_LAZY, RTLD_NOLOAD,
 	dlopen, dlsym, dlclose,
-	dlerror,
-};
-use ::std::{
-	error::Error as StdError,
-	ffi::{
-		CStr, CString,
-	},
-	fmt::{
-		self, Write,
-	},
 };
+use ::std::ffi::CStr;
 
-use super::UnixObject;
+use super::{
+	Error, LoaderError, UnixObject,
+};
 
 #[derive(Debug)]
 pub struct Library {
@@ -30,6 +24,11 @@ impl Library {
 		}
 	}
 }
+impl Library {
+	pub fn symbol_versioned(&self, name: &CStr, version: &CStr) -> *mut c_void {
+		self.symbols.symbol_versioned(name, version)
+	}
+}
 impl super::super::LibraryImpl for Library {
 	fn base_addr(&self) -> usize {
 		self.base_addr
@@ -44,15 +43,24 @@ pub struct Symbols {
 	handle: *mut c_void,
 }
 impl Symbols {
+	/// Opens a handle to the already loaded object named `filename`.
+	/// 
+	/// An empty `filename` refers to the main program.
 	pub fn open(filename: &CStr) -> Result<Self, Error> {
 		unsafe {
-			let handle = dlopen(filename.as_ptr(), RTLD_LAZY | RTLD_NOLOAD);
+			// Clear any stale error of this thread so that the one that is taken below belongs to this call.
+			let _ = LoaderError::take();
+			let filename = if filename.is_empty() { null() } else { filename.as_ptr() };
+			let handle = dlopen(filename, RTLD_LAZY | RTLD_NOLOAD);
 			if !handle.is_null() {
 				Ok(Self {
 					handle,
 				})
 			} else {
-				Err(Error::last_error())
+				match LoaderError::take() {
+					Some(error) => Err(Error::Loader(error)),
+					None => Err(Error::NotFound),
+				}
 			}
 		}
 	}
@@ -60,42 +68,47 @@ impl Symbols {
 	pub fn symbol(&self, name: &CStr) -> *mut c_void {
 		unsafe { dlsym(self.handle, name.as_ptr()) }
 	}
-}
-impl Drop for Symbols {
-	fn drop(&mut self) {
-		unsafe { dlclose(self.handle) };
+
+	/// Finds the symbol named `name` with the given `version` using `dlvsym`.
+	/// 
+	/// On platforms without `dlvsym`, this always returns a null pointer.
+	pub fn symbol_versioned(&self, name: &CStr, version: &CStr) -> *mut c_void {
+		dlvsym_in(self.handle, name, version)
 	}
 }
 
-#[derive(Debug)]
-#[repr(transparent)]
-pub struct Error(CString);
-impl Error {
-	pub fn last_error() -> Self {
-		let message = unsafe {
-			let message_ptr = dlerror();
-			if !message_ptr.is_null() {
-				CStr::from_ptr(message_ptr)
-			} else {
-				c"(no information available)"
-			}
-		};
-		Self::from_c_str(message)
-	}
+/// Finds the symbol named `name` in the global scope of the process,
+/// which is where the loader looks first when it binds imports.
+pub fn global_symbol(name: &CStr) -> *mut c_void {
+	unsafe { dlsym(RTLD_DEFAULT, name.as_ptr()) }
+}
 
-	fn from_c_str(s: &CStr) -> Self {
-		Self(CString::from(s))
+/// Finds the symbol named `name` with the given `version` in the global scope of the process.
+/// 
+/// On platforms without `dlvsym`, this always returns a null pointer.
+pub fn global_symbol_versioned(name: &CStr, version: &CStr) -> *mut c_void {
+	dlvsym_in(RTLD_DEFAULT, name, version)
+}
+
+fn dlvsym_in(handle: *mut c_void, name: &CStr, version: &CStr) -> *mut c_void {
+	#[cfg(any(
+		all(target_os = "linux", target_env = "gnu"),
+		target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd",
+	))]
+	unsafe {
+		::libc::dlvsym(handle, name.as_ptr(), version.as_ptr())
+	}
+	#[cfg(not(any(
+		all(target_os = "linux", target_env = "gnu"),
+		target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd",
+	)))]
+	{
+		let _ = (handle, name, version);
+		::core::ptr::null_mut()
 	}
 }
-impl fmt::Display for Error {
-	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		for chunk in self.0.as_bytes().utf8_chunks() {
-			f.write_str(chunk.valid())?;
-			for _ in chunk.invalid() {
-				f.write_char(char::REPLACEMENT_CHARACTER)?;
-			}
-		}
-		Ok(())
+impl Drop for Symbols {
+	fn drop(&mut self) {
+		unsafe { dlclose(self.handle) };
 	}
 }
-impl StdError for Error {}
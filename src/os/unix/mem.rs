@@ -0,0 +1,349 @@
+use ::core::ops::Range;
+use ::libc::{
+	c_int, c_void,
+	MAP_ANONYMOUS, MAP_FAILED, MAP_PRIVATE, PROT_EXEC, PROT_READ, PROT_WRITE, _SC_PAGESIZE,
+	mmap, mprotect, munmap, sysconf,
+};
+use ::std::sync::Mutex;
+
+use super::Error;
+
+/// Lock that is held while the protection of pages is changed to write to them,
+/// so that one write never makes a page read-only again while another one is in progress.
+static PROTECT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Largest distance that a `rel32` displacement can reach.
+const REL32_REACH: usize = 1 << 31;
+
+/// Distance between the addresses that [`map_near`] tries when `/proc/self/maps` can't be read,
+/// which is also kept from the edges of the reach of `rel32` displacements.
+const MAP_NEAR_STEP: usize = 1 << 20;
+
+/// Returns the size of a page of memory.
+pub fn page_size() -> usize {
+	unsafe { sysconf(_SC_PAGESIZE) as usize }
+}
+
+/// Returns the range of whole pages that contain `range`.
+pub fn page_range(range: Range<usize>) -> Range<usize> {
+	let page_size = page_size();
+	(range.start & !(page_size - 1))..range.end.next_multiple_of(page_size)
+}
+
+/// Changes the protection of the pages that contain `range` to `prot` (such as `PROT_READ | PROT_EXEC`).
+/// 
+/// # Safety
+/// The pages must be mapped, and no code may rely on their previous protection.
+pub unsafe fn protect(range: Range<usize>, prot: c_int) -> Result<(), Error> {
+	let pages = page_range(range);
+	match unsafe { mprotect(pages.start as *mut c_void, pages.len(), prot) } {
+		0 => Ok(()),
+		_ => Err(Error::last_os_error()),
+	}
+}
+
+/// Changes the protection of the pages that contain `range` to `prot` for the duration of `f`,
+/// and then to `restore`.
+/// 
+/// A process-wide lock is held during the call,
+/// so that concurrent calls for the same pages don't restore the protection while `f` is running.
+/// 
+/// # Safety
+/// See [`protect`].
+pub unsafe fn with_protection<R, F>(range: Range<usize>, prot: c_int, restore: c_int, f: F) -> Result<R, Error>
+where
+	F: FnOnce() -> R,
+{
+	let _guard = PROTECT_LOCK.lock().unwrap_or_else(move |poisoned| poisoned.into_inner());
+	unsafe {
+		protect(range.clone(), prot)?;
+		let result = f();
+		protect(range, restore)?;
+		Ok(result)
+	}
+}
+
+/// Returns the range of addresses in which memory can be placed
+/// so that `rel32` displacements from anywhere in it can reach all of `near`.
+fn near_window(near: &Range<usize>) -> Range<usize> {
+	let reach = REL32_REACH - MAP_NEAR_STEP;
+	near.end.saturating_sub(reach)..near.start.saturating_add(reach)
+}
+
+/// Returns `true` if `rel32` displacements from anywhere in `range` can reach all of `near`.
+pub fn is_near(near: &Range<usize>, range: &Range<usize>) -> bool {
+	let window = near_window(near);
+	window.start <= range.start && range.end <= window.end
+}
+
+/// Returns the ranges of memory that are currently mapped into the process, sorted by address,
+/// or `None` if `/proc/self/maps` can't be read.
+fn mapped_ranges() -> Option<Vec<Range<usize>>> {
+	let maps = ::std::fs::read_to_string("/proc/self/maps").ok()?;
+	let mut ranges = maps.lines()
+		.map(move |line| {
+			let (start, end) = line.split_once(' ')?.0.split_once('-')?;
+			Some(usize::from_str_radix(start, 16).ok()?..usize::from_str_radix(end, 16).ok()?)
+		})
+		.collect::<Option<Vec<_>>>()?;
+	ranges.sort_by_key(move |range| range.start);
+	Some(ranges)
+}
+
+/// Returns the addresses at which `len` bytes could be mapped within reach of `near`, closest first.
+/// 
+/// These are taken from the gaps between the mappings in `/proc/self/maps` when it can be read,
+/// and are otherwise spaced out by [`MAP_NEAR_STEP`] around `near`.
+fn near_hints(near: &Range<usize>, len: usize) -> Vec<usize> {
+	let page_size = page_size();
+	let window = near_window(near);
+	let Some(mapped) = mapped_ranges() else {
+		let start = near.start & !(MAP_NEAR_STEP - 1);
+		let end = near.end.next_multiple_of(MAP_NEAR_STEP);
+		let below_len = len.next_multiple_of(MAP_NEAR_STEP);
+		// Try the addresses right after and before the range first, moving outwards.
+		return (0..REL32_REACH / MAP_NEAR_STEP)
+			.flat_map(move |i| [end.checked_add(i * MAP_NEAR_STEP), start.checked_sub(below_len + i * MAP_NEAR_STEP)])
+			.flatten()
+			.filter(|&hint| hint != 0 && is_near(near, &(hint..hint + len)))
+			.collect()
+	};
+
+	let mut hints = Vec::new();
+	// The lowest addresses can't be mapped by default, see `vm.mmap_min_addr`.
+	let mut gap_start = MAP_NEAR_STEP;
+	for range in mapped.into_iter().chain(Some(usize::MAX..usize::MAX)) {
+		let gap = gap_start..range.start;
+		gap_start = gap_start.max(range.end);
+		let start = gap.start.max(window.start).next_multiple_of(page_size);
+		let end = gap.end.min(window.end) & !(page_size - 1);
+		if start >= end || end - start < len {
+			continue
+		}
+		// Take the end of the gap that is closest to `near`.
+		let (hint, distance) = if start >= near.end {
+			(start, start - near.end)
+		} else if end <= near.start {
+			(end - len, near.start - end)
+		} else {
+			(start, 0)
+		};
+		hints.push((distance, hint));
+	}
+	hints.sort_unstable();
+	hints.into_iter().map(move |(_, hint)| hint).collect()
+}
+
+/// Maps `len` bytes within reach of `near`, trying every address from [`near_hints`].
+fn mmap_near(near: &Range<usize>, len: usize, prot: c_int, flags: c_int, fd: c_int) -> Result<usize, Error> {
+	for hint in near_hints(near, len) {
+		let addr = unsafe { mmap(hint as *mut c_void, len, prot, flags | MAP_NEAR_FLAGS, fd, 0) };
+		if addr == MAP_FAILED {
+			continue
+		}
+		let addr = addr as usize;
+		if is_near(near, &(addr..addr + len)) {
+			return Ok(addr)
+		}
+		unsafe { munmap(addr as *mut c_void, len) };
+	}
+	Err(Error::NotFound)
+}
+
+/// Flags that make `mmap` fail instead of choosing another address when the hint is taken.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MAP_NEAR_FLAGS: c_int = ::libc::MAP_FIXED_NOREPLACE;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const MAP_NEAR_FLAGS: c_int = 0;
+
+/// Memory that was mapped by [`map_near`] or [`map_code_near`].
+#[derive(Debug)]
+pub struct NearMapping {
+	addr: usize,
+	len: usize,
+	alias: Option<usize>,
+}
+
+impl NearMapping {
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	pub const fn len(&self) -> usize {
+		self.len
+	}
+
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub const fn range(&self) -> Range<usize> {
+		self.addr..self.addr + self.len
+	}
+
+	/// Returns the address of a second, writable view of executable memory, if it has one.
+	/// 
+	/// Code is written through this view, so that the executable one never has to become writable.
+	pub const fn alias(&self) -> Option<usize> {
+		self.alias
+	}
+
+	/// Unmaps the memory, along with its writable view.
+	/// 
+	/// # Safety
+	/// Nothing may use the memory afterwards.
+	pub unsafe fn unmap(self) -> Result<(), Error> {
+		let result = match unsafe { munmap(self.addr as *mut c_void, self.len) } {
+			0 => Ok(()),
+			_ => Err(Error::last_os_error()),
+		};
+		if let Some(alias) = self.alias {
+			unsafe { munmap(alias as *mut c_void, self.len) };
+		}
+		result
+	}
+}
+
+/// Maps at least `len` bytes of readable and writable memory
+/// from which `rel32` displacements can reach all of `near`.
+/// 
+/// The free address space is found by reading `/proc/self/maps`.
+/// If it can't be read, such as on platforms without `procfs`,
+/// addresses around `near` are tried one after another.
+pub fn map_near(near: Range<usize>, len: usize) -> Result<NearMapping, Error> {
+	let len = len.next_multiple_of(page_size());
+	let addr = mmap_near(&near, len, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1)?;
+	Ok(NearMapping {
+		addr,
+		len,
+		alias: None,
+	})
+}
+
+/// Maps at least `len` bytes of readable and executable memory
+/// from which `rel32` displacements can reach all of `near`.
+/// 
+/// # Platform usage
+/// On Linux and Android, the memory is backed by a `memfd` that is also mapped elsewhere as writable,
+/// which is the [`alias`](NearMapping::alias) that code is written through.
+/// Elsewhere, or if the `memfd` can't be created, the memory has no alias,
+/// and has to be made writable and then executable again to write to it.
+/// The memory is never writable and executable at the same time.
+pub fn map_code_near(near: Range<usize>, len: usize) -> Result<NearMapping, Error> {
+	let len = len.next_multiple_of(page_size());
+	#[cfg(any(target_os = "linux", target_os = "android"))]
+	if let Some(mapping) = map_aliased_near(&near, len)? {
+		return Ok(mapping)
+	}
+	let mapping = map_near(near, len)?;
+	match unsafe { protect(mapping.range(), PROT_READ | PROT_EXEC) } {
+		Ok(()) => Ok(mapping),
+		Err(error) => {
+			unsafe { let _ = mapping.unmap(); }
+			Err(error)
+		}
+	}
+}
+
+/// Maps a `memfd` of `len` bytes as executable near `near` and as writable anywhere,
+/// returning `None` if no `memfd` can be created.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn map_aliased_near(near: &Range<usize>, len: usize) -> Result<Option<NearMapping>, Error> {
+	use ::libc::{
+		close, ftruncate, memfd_create,
+		MAP_SHARED, MFD_CLOEXEC,
+	};
+
+	let fd = unsafe { memfd_create(c"loaded-code".as_ptr(), MFD_CLOEXEC) };
+	if fd < 0 {
+		return Ok(None)
+	}
+	let result = (|| unsafe {
+		if ftruncate(fd, len as _) != 0 {
+			return Err(Error::last_os_error())
+		}
+		let addr = mmap_near(near, len, PROT_READ | PROT_EXEC, MAP_SHARED, fd)?;
+		let alias = mmap(::core::ptr::null_mut(), len, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+		if alias == MAP_FAILED {
+			let error = Error::last_os_error();
+			munmap(addr as *mut c_void, len);
+			return Err(error)
+		}
+		Ok(NearMapping {
+			addr,
+			len,
+			alias: Some(alias as usize),
+		})
+	})();
+	// The mappings keep the memory alive.
+	unsafe { close(fd) };
+	result.map(Some)
+}
+
+/// Copies the memory at `addr` into `buffer`,
+/// returning an error instead of faulting if any of it is not readable.
+/// 
+/// # Platform usage
+/// On Linux and Android, this uses `process_vm_readv` on the current process.
+/// On other Unix platforms, the memory is written to a pipe and read back,
+/// since `write` fails with `EFAULT` for unreadable memory.
+pub fn read_memory(addr: usize, buffer: &mut [u8]) -> Result<(), Error> {
+	if buffer.is_empty() {
+		return Ok(())
+	}
+	read_memory_imp(addr, buffer)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_memory_imp(addr: usize, buffer: &mut [u8]) -> Result<(), Error> {
+	use ::libc::{
+		iovec, getpid, process_vm_readv,
+	};
+
+	let local = iovec {
+		iov_base: buffer.as_mut_ptr() as *mut c_void,
+		iov_len: buffer.len(),
+	};
+	let remote = iovec {
+		iov_base: addr as *mut c_void,
+		iov_len: buffer.len(),
+	};
+	let read = unsafe { process_vm_readv(getpid(), &local, 1, &remote, 1, 0) };
+	match read {
+		-1 => Err(Error::last_os_error()),
+		read if read as usize == buffer.len() => Ok(()),
+		_ => Err(Error::Os(::std::io::Error::from_raw_os_error(::libc::EFAULT))),
+	}
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn read_memory_imp(addr: usize, buffer: &mut [u8]) -> Result<(), Error> {
+	use ::libc::{
+		close, pipe, read, write,
+	};
+
+	let mut fds = [0; 2];
+	if unsafe { pipe(fds.as_mut_ptr()) } != 0 {
+		return Err(Error::last_os_error())
+	}
+	let [read_fd, write_fd] = fds;
+	let result = (|| {
+		// Pipes can hold at least a page, so the chunks never block.
+		for (offset, chunk) in (0..buffer.len()).step_by(512).zip(buffer.chunks_mut(512)) {
+			let written = unsafe { write(write_fd, (addr + offset) as *const c_void, chunk.len()) };
+			if written != chunk.len() as isize {
+				return Err(Error::last_os_error())
+			}
+			let read = unsafe { read(read_fd, chunk.as_mut_ptr() as *mut c_void, chunk.len()) };
+			if read != chunk.len() as isize {
+				return Err(Error::last_os_error())
+			}
+		}
+		Ok(())
+	})();
+	unsafe {
+		close(read_fd);
+		close(write_fd);
+	}
+	result
+}
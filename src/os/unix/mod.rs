@@ -1,10 +1,12 @@
 use ::core::{
 	ffi::CStr,
 	fmt,
-	mem::ManuallyDrop,
+	mem::{
+		ManuallyDrop, MaybeUninit,
+	},
 	num::NonZero,
 	ops::{
-		ControlFlow,
+		ControlFlow, Range,
 		BitAnd, BitOr,
 	},
 	slice::{
@@ -12,16 +14,45 @@ use ::core::{
 	},
 };
 use ::libc::{
-	dl_iterate_phdr,
-	dl_phdr_info,
+	dl_iterate_phdr, dladdr,
+	dl_phdr_info, Dl_info,
 	c_int, c_void, size_t,
 	PF_X, PF_W, PF_R,
+	PROT_NONE, PROT_EXEC, PROT_WRITE, PROT_READ,
+	PT_NULL, PT_LOAD, PT_DYNAMIC, PT_INTERP, PT_NOTE, PT_SHLIB, PT_PHDR, PT_TLS,
+	PT_GNU_EH_FRAME, PT_GNU_STACK, PT_GNU_RELRO,
 };
+use ::std::ffi::CString;
 
-use crate::map::*;
+use crate::{
+	map::*,
+	SegmentKind,
+};
 
+mod error;
+pub use error::*;
 mod library;
 pub use library::*;
+mod owned;
+pub use owned::*;
+mod note;
+pub use note::*;
+mod dynamic;
+pub use dynamic::*;
+mod hash;
+pub use hash::*;
+mod symtab;
+pub use symtab::*;
+mod version;
+pub use version::*;
+mod eh_frame;
+pub use eh_frame::*;
+mod mem;
+pub use mem::*;
+mod reloc;
+pub use reloc::*;
+mod plt;
+pub use plt::*;
 
 macro_rules! for_each_object_callback {
 	{
@@ -51,13 +82,16 @@ macro_rules! for_each_object_callback {
 	};
 }
 
-pub use ::core::convert::Infallible as Error;
-
 pub(crate) type ModuleName<'a> = &'a CStr;
 
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct Object<'a>(&'a UnixObject);
+impl<'a> Object<'a> {
+	pub const fn as_unix_object(&self) -> &'a UnixObject {
+		self.0
+	}
+}
 impl super::ObjectImpl for Object<'_> {
 	fn is_main_program(&self) -> bool {
 		self.0.is_main_program()
@@ -66,35 +100,52 @@ impl super::ObjectImpl for Object<'_> {
 		self.0.base_addr()
 	}
 	fn segments(&self) -> Segments<'_> {
-		Segments {
-			headers: self.0.headers().iter(),
-		}
+		Segments::new(self.0)
 	}
-	fn symbols(&self) -> Symbols {
-		match Symbols::open(self.0.name()) {
-			Ok(symbols) => symbols,
-			Err(error) => {
-				panic!("`dlopen` on a loaded object failed: {error}")
-			}
-		}
+	fn symbols(&self) -> Result<Symbols, Error> {
+		Symbols::open(self.0.name())
 	}
 	fn symbol(&self, symbols: &Symbols, name: &CStr) -> *mut () {
 		symbols.symbol(name) as _
 	}
-	fn library(&self, symbols: Symbols) -> Library {
-		Library::new(self.0, symbols)
+	fn lookup_symbol(&self, name: &CStr) -> *mut () {
+		match self.0.lookup_symbol(name) {
+			Some(symbol) => symbol.addr() as _,
+			None => ::core::ptr::null_mut(),
+		}
+	}
+	fn function_containing(&self, addr: usize) -> Option<Range<usize>> {
+		self.0.function_containing(addr)
+	}
+	fn functions(&self) -> Functions<'_> {
+		self.0.functions()
+	}
+	fn library(&self, symbols: Symbols) -> Result<Library, Error> {
+		Ok(Library::new(self.0, symbols))
 	}
 }
 
-#[repr(transparent)]
 pub struct Segments<'a> {
 	headers: ::core::slice::Iter<'a, ElfSegmentHeader>,
+	base_addr: usize,
+}
+
+impl<'a> Segments<'a> {
+	pub fn new(object: &'a UnixObject) -> Self {
+		Self {
+			headers: object.headers().iter(),
+			base_addr: object.base_addr(),
+		}
+	}
 }
 
 impl<'a> Iterator for Segments<'a> {
 	type Item = Segment<'a>;
 	fn next(&mut self) -> Option<Self::Item> {
-		self.headers.next()
+		self.headers.next().map(|header| Segment {
+			header,
+			base_addr: self.base_addr,
+		})
 	}
 }
 
@@ -120,6 +171,12 @@ impl super::ObjectsImpl for Objects {
 		Objects::for_each_object(self, &mut move |object| f(object.name(), Object(object)));
 		Ok(())
 	}
+	fn nearest_symbol(&self, addr: usize) -> Option<(CString, usize)> {
+		Objects::nearest_symbol(self, addr)
+	}
+	fn generation(&self) -> Option<(u64, u64)> {
+		Objects::generation(self)
+	}
 }
 
 impl Objects {
@@ -176,6 +233,34 @@ impl Objects {
 		});
 	}
 
+	/// Returns the name and address of the exported symbol closest to and below `addr`,
+	/// as reported by `dladdr`.
+	pub fn nearest_symbol(&self, addr: usize) -> Option<(CString, usize)> {
+		unsafe {
+			let mut info = MaybeUninit::<Dl_info>::zeroed();
+			if dladdr(addr as *const c_void, info.as_mut_ptr()) == 0 {
+				return None
+			}
+			let info = info.assume_init();
+			if info.dli_sname.is_null() {
+				return None
+			}
+			Some((CString::from(CStr::from_ptr(info.dli_sname)), info.dli_saddr as usize))
+		}
+	}
+
+	/// Returns the number of times that objects have been loaded and unloaded, in that order.
+	/// 
+	/// If either of the counters changes, then the set of loaded objects has changed.
+	pub fn generation(&self) -> Option<(u64, u64)> {
+		let mut generation = None;
+		let _ = self.for_each_object(&mut |object: &UnixObject| {
+			generation = Some((object.adds(), object.subs()));
+			true
+		});
+		generation
+	}
+
 	pub fn for_each_object<R, F>(&self, f: &mut F) -> R
 	where
 		R: ForEachObjectResult,
@@ -211,6 +296,16 @@ impl UnixObject {
 		unsafe { CStr::from_ptr(self.0.dlpi_name) }
 	}
 
+	/// Returns the number of objects that have been loaded into the process.
+	pub const fn adds(&self) -> u64 {
+		self.0.dlpi_adds as _
+	}
+
+	/// Returns the number of objects that have been unloaded from the process.
+	pub const fn subs(&self) -> u64 {
+		self.0.dlpi_subs as _
+	}
+
 	pub const fn n_headers(&self) -> usize {
 		self.0.dlpi_phnum as _
 	}
@@ -219,6 +314,20 @@ impl UnixObject {
 		unsafe { from_raw_parts(self.0.dlpi_phdr as *const ElfSegmentHeader, self.n_headers()) }
 	}
 
+	/// Returns the absolute range of addresses covered by the loadable segments of the object.
+	pub fn load_range(&self) -> Range<usize> {
+		let mut start = usize::MAX;
+		let mut end = 0;
+		for header in self.headers().iter().filter(move |header| header.is_loadable()) {
+			start = start.min(header.virtual_addr());
+			end = end.max(header.virtual_addr().wrapping_add(header.size()));
+		}
+		if start > end {
+			return 0..0
+		}
+		self.base_addr().wrapping_add(start)..self.base_addr().wrapping_add(end)
+	}
+
 	pub const fn headers_mut(&mut self) -> &mut [ElfSegmentHeader] {
 		unsafe { from_raw_parts_mut(self.0.dlpi_phdr as *mut ElfSegmentHeader, self.n_headers()) }
 	}
@@ -238,6 +347,36 @@ super::transparent_wrapper! {
 	pub struct ElfSegmentHeader for ElfPhdr;
 }
 impl ElfSegmentHeader {
+	pub const fn raw_kind(&self) -> ElfWord {
+		self.0.p_type
+	}
+
+	pub const fn kind(&self) -> SegmentKind {
+		match self.raw_kind() {
+			PT_NULL => SegmentKind::Null,
+			PT_LOAD => SegmentKind::Load,
+			PT_DYNAMIC => SegmentKind::Dynamic,
+			PT_INTERP => SegmentKind::Interp,
+			PT_NOTE => SegmentKind::Note,
+			PT_SHLIB => SegmentKind::Shlib,
+			PT_PHDR => SegmentKind::Phdr,
+			PT_TLS => SegmentKind::Tls,
+			PT_GNU_EH_FRAME => SegmentKind::GnuEhFrame,
+			PT_GNU_STACK => SegmentKind::GnuStack,
+			PT_GNU_RELRO => SegmentKind::GnuRelro,
+			PT_GNU_PROPERTY => SegmentKind::GnuProperty,
+			other => SegmentKind::Other(other as _),
+		}
+	}
+
+	pub const fn is_loadable(&self) -> bool {
+		self.raw_kind() == PT_LOAD
+	}
+
+	pub const fn file_offset(&self) -> usize {
+		self.0.p_offset as _
+	}
+
 	pub const fn virtual_addr(&self) -> usize {
 		self.0.p_vaddr as _
 	}
@@ -246,24 +385,55 @@ impl ElfSegmentHeader {
 		SegmentFlags(self.0.p_flags)
 	}
 
+	pub const fn file_size(&self) -> usize {
+		self.0.p_filesz as _
+	}
+
 	pub const fn size(&self) -> usize {
 		self.0.p_memsz as _
 	}
+
+	pub const fn align(&self) -> usize {
+		self.0.p_align as _
+	}
 }
 
-pub(crate) type Segment<'a> = &'a ElfSegmentHeader;
+/// Program header of a loaded object, along with the base address of that object.
+#[derive(Clone, Copy)]
+pub struct Segment<'a> {
+	pub header: &'a ElfSegmentHeader,
+	pub base_addr: usize,
+}
 impl super::SegmentImpl for Segment<'_> {
+	fn kind(&self) -> SegmentKind {
+		self.header.kind()
+	}
 	fn flags(&self) -> self::SegmentFlags {
-		ElfSegmentHeader::flags(self)
+		self.header.flags()
+	}
+	fn base_addr(&self) -> usize {
+		self.base_addr
+	}
+	fn file_offset(&self) -> usize {
+		self.header.file_offset()
+	}
+	fn file_size(&self) -> usize {
+		self.header.file_size()
 	}
 	fn virtual_addr(&self) -> usize {
-		ElfSegmentHeader::virtual_addr(self)
+		self.header.virtual_addr()
 	}
 	fn size(&self) -> usize {
-		ElfSegmentHeader::size(self)
+		self.header.size()
+	}
+	fn align(&self) -> usize {
+		self.header.align()
 	}
 }
 
+/// `p_type` of program headers that describe the GNU property note.
+pub const PT_GNU_PROPERTY: ElfWord = 0x6474e553;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct SegmentFlags(pub ElfWord);
@@ -310,6 +480,21 @@ impl SegmentFlags {
 	pub const fn is_readable(&self) -> bool {
 		self.contains(&Self::READABLE)
 	}
+
+	/// Returns the protection that the loader maps segments with these flags with, for `mprotect`.
+	pub const fn prot(&self) -> c_int {
+		let mut prot = PROT_NONE;
+		if self.is_readable() {
+			prot |= PROT_READ;
+		}
+		if self.is_writable() {
+			prot |= PROT_WRITE;
+		}
+		if self.is_executable() {
+			prot |= PROT_EXEC;
+		}
+		prot
+	}
 }
 
 impl BitAnd for SegmentFlags {
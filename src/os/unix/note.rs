@@ -0,0 +1,243 @@
+use ::core::{
+	fmt,
+	slice::from_raw_parts,
+	str::from_utf8,
+};
+
+use crate::SegmentKind;
+
+use super::{
+	Segments, UnixObject,
+};
+
+/// Note type of the GNU ABI tag, under the name `GNU`.
+pub const NT_GNU_ABI_TAG: u32 = 1;
+/// Note type of the GNU build ID, under the name `GNU`.
+pub const NT_GNU_BUILD_ID: u32 = 3;
+/// Note type of the GNU program properties, under the name `GNU`.
+pub const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+/// Note type of the package metadata in `.note.package`, under the name `FDO`.
+pub const NT_FDO_PACKAGING_METADATA: u32 = 0xcafe1a7e;
+
+/// GNU program property with x86 features that are enabled in all of the linked inputs.
+pub const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc0000002;
+/// GNU program property with x86 ISA levels that are needed by the object.
+pub const GNU_PROPERTY_X86_ISA_1_NEEDED: u32 = 0xc0008002;
+
+const NOTE_HEADER_SIZE: usize = 12;
+
+impl UnixObject {
+	/// Returns an iterator over the notes in all of the `PT_NOTE` segments of the object.
+	pub fn notes(&self) -> Notes<'_> {
+		Notes {
+			segments: Segments::new(self),
+			data: &[],
+			align: 4,
+		}
+	}
+
+	/// Returns the GNU build ID of the object, if it has one.
+	pub fn build_id(&self) -> Option<&[u8]> {
+		self.notes().find_map(move |note| note.build_id())
+	}
+
+	/// Returns the GNU ABI tag of the object, if it has one.
+	pub fn abi_tag(&self) -> Option<AbiTag> {
+		self.notes().find_map(move |note| note.abi_tag())
+	}
+
+	/// Returns the GNU program properties of the object, if it has any.
+	pub fn gnu_properties(&self) -> Option<GnuProperties<'_>> {
+		self.notes().find_map(move |note| note.gnu_properties())
+	}
+
+	/// Returns the JSON package metadata from the `.note.package` section of the object, if it has one.
+	pub fn package_metadata(&self) -> Option<&str> {
+		self.notes().find_map(move |note| note.package_metadata())
+	}
+}
+
+/// Iterator over the notes of an object.
+/// 
+/// See [`UnixObject::notes`].
+pub struct Notes<'a> {
+	segments: Segments<'a>,
+	data: &'a [u8],
+	align: usize,
+}
+
+impl<'a> Iterator for Notes<'a> {
+	type Item = Note<'a>;
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some((note, rest)) = Note::parse(self.data, self.align) {
+				self.data = rest;
+				return Some(note)
+			}
+
+			let segment = self.segments.find(move |segment| segment.header.kind() == SegmentKind::Note)?;
+			self.data = unsafe {
+				from_raw_parts(
+					segment.base_addr.wrapping_add(segment.header.virtual_addr()) as *const u8,
+					segment.header.size(),
+				)
+			};
+			self.align = if segment.header.align() == 8 { 8 } else { 4 };
+		}
+	}
+}
+
+/// Single ELF note.
+#[derive(Clone, Copy)]
+pub struct Note<'a> {
+	name: &'a [u8],
+	kind: u32,
+	desc: &'a [u8],
+}
+
+impl<'a> Note<'a> {
+	/// Parses the note at the start of `data`,
+	/// returning it along with the data that follows it.
+	/// 
+	/// `align` is the alignment of the note entries, which is either 4 or 8.
+	pub fn parse(data: &'a [u8], align: usize) -> Option<(Self, &'a [u8])> {
+		let name_size = read_u32(data, 0)? as usize;
+		let desc_size = read_u32(data, 4)? as usize;
+		let kind = read_u32(data, 8)?;
+
+		let name = data.get(NOTE_HEADER_SIZE..NOTE_HEADER_SIZE.checked_add(name_size)?)?;
+		let desc_offset = align_up(NOTE_HEADER_SIZE + name_size, align);
+		let desc = data.get(desc_offset..desc_offset.checked_add(desc_size)?)?;
+		let next_offset = align_up(desc_offset + desc_size, align).min(data.len());
+
+		let note = Self {
+			name: name.strip_suffix(b"\0").unwrap_or(name),
+			kind,
+			desc,
+		};
+		Some((note, &data[next_offset..]))
+	}
+
+	/// Returns the name of the note's owner, without the terminating nul byte.
+	pub const fn name(&self) -> &'a [u8] {
+		self.name
+	}
+
+	/// Returns the type of the note, which is interpreted according to its [`name`](Self::name).
+	pub const fn kind(&self) -> u32 {
+		self.kind
+	}
+
+	/// Returns the descriptor of the note.
+	pub const fn desc(&self) -> &'a [u8] {
+		self.desc
+	}
+
+	/// Returns `true` if the note has the given `name` and `kind`.
+	pub fn is(&self, name: &[u8], kind: u32) -> bool {
+		self.name == name && self.kind == kind
+	}
+
+	/// Returns the GNU build ID if this is a `NT_GNU_BUILD_ID` note.
+	pub fn build_id(&self) -> Option<&'a [u8]> {
+		self.is(b"GNU", NT_GNU_BUILD_ID).then_some(self.desc)
+	}
+
+	/// Returns the GNU ABI tag if this is a `NT_GNU_ABI_TAG` note.
+	pub fn abi_tag(&self) -> Option<AbiTag> {
+		if !self.is(b"GNU", NT_GNU_ABI_TAG) {
+			return None
+		}
+		Some(AbiTag {
+			os: read_u32(self.desc, 0)?,
+			major: read_u32(self.desc, 4)?,
+			minor: read_u32(self.desc, 8)?,
+			patch: read_u32(self.desc, 12)?,
+		})
+	}
+
+	/// Returns the GNU program properties if this is a `NT_GNU_PROPERTY_TYPE_0` note.
+	pub fn gnu_properties(&self) -> Option<GnuProperties<'a>> {
+		self.is(b"GNU", NT_GNU_PROPERTY_TYPE_0).then_some(GnuProperties {
+			data: self.desc,
+		})
+	}
+
+	/// Returns the JSON package metadata if this is a `NT_FDO_PACKAGING_METADATA` note.
+	pub fn package_metadata(&self) -> Option<&'a str> {
+		if !self.is(b"FDO", NT_FDO_PACKAGING_METADATA) {
+			return None
+		}
+		let desc = self.desc.split(move |&b| b == 0).next()?;
+		from_utf8(desc).ok()
+	}
+}
+
+impl fmt::Debug for Note<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Note")
+			.field("name", &String::from_utf8_lossy(self.name))
+			.field("kind", &format_args!("{:#x}", self.kind))
+			.field("desc_len", &self.desc.len())
+			.finish()
+	}
+}
+
+/// Contents of a `NT_GNU_ABI_TAG` note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AbiTag {
+	/// Operating system that the object was built for, where `0` is Linux.
+	pub os: u32,
+	pub major: u32,
+	pub minor: u32,
+	pub patch: u32,
+}
+
+/// Iterator over the properties in a `NT_GNU_PROPERTY_TYPE_0` note.
+#[derive(Debug, Clone)]
+pub struct GnuProperties<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> Iterator for GnuProperties<'a> {
+	type Item = GnuProperty<'a>;
+	fn next(&mut self) -> Option<Self::Item> {
+		let kind = read_u32(self.data, 0)?;
+		let size = read_u32(self.data, 4)? as usize;
+		let data = self.data.get(8..8usize.checked_add(size)?)?;
+		let next_offset = align_up(8 + size, size_of::<usize>()).min(self.data.len());
+		self.data = &self.data[next_offset..];
+		Some(GnuProperty {
+			kind,
+			data,
+		})
+	}
+}
+
+/// Single property in a `NT_GNU_PROPERTY_TYPE_0` note.
+#[derive(Debug, Clone, Copy)]
+pub struct GnuProperty<'a> {
+	pub kind: u32,
+	pub data: &'a [u8],
+}
+
+impl GnuProperty<'_> {
+	/// Returns the data of the property as a single `u32`,
+	/// which is how most bit mask properties are stored.
+	pub fn as_u32(&self) -> Option<u32> {
+		if self.data.len() == 4 {
+			read_u32(self.data, 0)
+		} else {
+			None
+		}
+	}
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+	let bytes = data.get(offset..offset.checked_add(4)?)?;
+	Some(u32::from_ne_bytes(bytes.try_into().ok()?))
+}
+
+const fn align_up(value: usize, align: usize) -> usize {
+	(value + align - 1) & !(align - 1)
+}
@@ -0,0 +1,77 @@
+use ::core::{
+	ffi::CStr,
+	fmt,
+	ptr::null_mut,
+};
+use ::std::ffi::CString;
+
+use super::{
+	ElfSegmentHeader, UnixObject,
+	Object, Segments,
+};
+
+/// Copy of a [`UnixObject`] that owns its name and program headers.
+/// 
+/// The inner `dl_phdr_info` points into the owned data,
+/// so it can be used anywhere a borrowed [`UnixObject`] is expected.
+pub struct OwnedUnixObject {
+	info: UnixObject,
+	name: CString,
+	headers: Box<[ElfSegmentHeader]>,
+}
+
+// SAFETY: All of the pointers in `info` point into data owned by the structure,
+// which is never mutated after construction.
+unsafe impl Send for OwnedUnixObject {}
+unsafe impl Sync for OwnedUnixObject {}
+
+impl OwnedUnixObject {
+	pub fn new(object: &UnixObject) -> Self {
+		let name = CString::from(object.name());
+		let headers: Box<[ElfSegmentHeader]> = object.headers().into();
+
+		let mut info = *object.as_inner();
+		info.dlpi_name = name.as_ptr();
+		info.dlpi_phdr = headers.as_ptr() as _;
+		info.dlpi_tls_data = null_mut();
+
+		Self {
+			info: UnixObject(info),
+			name,
+			headers,
+		}
+	}
+
+	pub const fn as_unix_object(&self) -> &UnixObject {
+		&self.info
+	}
+}
+
+impl Clone for OwnedUnixObject {
+	fn clone(&self) -> Self {
+		Self::new(self.as_unix_object())
+	}
+}
+
+impl fmt::Debug for OwnedUnixObject {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(self.as_unix_object(), f)
+	}
+}
+
+pub(crate) type OwnedObject = OwnedUnixObject;
+impl super::super::OwnedObjectImpl for OwnedObject {
+	fn new(name: &CStr, object: &Object<'_>) -> Self {
+		let _ = name;
+		Self::new(object.0)
+	}
+	fn name(&self) -> &CStr {
+		&self.name
+	}
+	fn object(&self) -> Object<'_> {
+		Object(self.as_unix_object())
+	}
+	fn segments(&self) -> Segments<'_> {
+		Segments::new(self.as_unix_object())
+	}
+}
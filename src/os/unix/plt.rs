@@ -0,0 +1,218 @@
+use ::core::{
+	ffi::CStr,
+	ops::Range,
+	sync::atomic::{
+		AtomicUsize, Ordering,
+	},
+};
+use ::libc::{
+	PROT_READ, PROT_WRITE,
+};
+
+use crate::SegmentKind;
+
+use super::{
+	Error, RelocationKind, Symbols, UnixObject,
+	global_symbol, global_symbol_versioned, page_size, with_protection,
+};
+
+impl UnixObject {
+	/// Returns the absolute range of the object's `PT_GNU_RELRO` segment,
+	/// which the loader makes read-only after relocating it.
+	pub fn relro_range(&self) -> Option<Range<usize>> {
+		let header = self.headers().iter().find(move |header| header.kind() == SegmentKind::GnuRelro)?;
+		let start = self.base_addr().wrapping_add(header.virtual_addr());
+		Some(start..start.wrapping_add(header.size()))
+	}
+
+	/// Returns the range of whole pages that the loader made read-only for `PT_GNU_RELRO`.
+	/// 
+	/// Like glibc, this rounds both ends down, since the page at the end is shared with writable data.
+	fn relro_pages(&self) -> Option<Range<usize>> {
+		let range = self.relro_range()?;
+		let mask = !(page_size() - 1);
+		Some((range.start & mask)..(range.end & mask))
+	}
+}
+
+/// GOT entry of an import that has been redirected by an [`ImportHook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImportSlot {
+	addr: usize,
+	kind: RelocationKind,
+	previous: usize,
+}
+
+impl ImportSlot {
+	/// Returns the absolute address of the GOT entry.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	/// Returns the kind of the relocation of the entry,
+	/// which is either [`JumpSlot`](RelocationKind::JumpSlot) or [`GlobDat`](RelocationKind::GlobDat).
+	pub const fn kind(&self) -> RelocationKind {
+		self.kind
+	}
+
+	/// Returns the value of the entry before it was hooked,
+	/// which is the address of a PLT stub if a lazy binding wasn't resolved yet.
+	pub const fn previous(&self) -> usize {
+		self.previous
+	}
+}
+
+/// Guard of the GOT entries of an import that have been redirected to a replacement,
+/// which restores them when dropped.
+/// 
+/// The guard also keeps the hooked object loaded.
+/// See [`Object::hook_import`](crate::Object::hook_import).
+#[derive(Debug)]
+pub struct ImportHook {
+	slots: Vec<ImportSlot>,
+	original: usize,
+	replacement: usize,
+	relro_pages: Option<Range<usize>>,
+	restored: bool,
+	_symbols: Symbols,
+}
+
+// SAFETY: The guard only refers to process-wide memory, and the handle in `Symbols` is only closed.
+unsafe impl Send for ImportHook {}
+unsafe impl Sync for ImportHook {}
+
+impl ImportHook {
+	/// Redirects the `JUMP_SLOT` and `GLOB_DAT` entries of the import named `name` in `object` to `replacement`.
+	/// 
+	/// `symbols` must be a handle to `object`, which keeps it loaded
+	/// and is used to resolve lazily bound imports that aren't in the global scope.
+	/// 
+	/// # Safety
+	/// See [`Object::hook_import`](crate::Object::hook_import).
+	pub unsafe fn new(object: &UnixObject, symbols: Symbols, name: &CStr, replacement: usize) -> Result<Self, Error> {
+		let dynamic = object.dynamic().ok_or(Error::NotFound)?;
+		let load_range = object.load_range();
+		let mut original = None;
+		let mut slots = Vec::new();
+		for relocation in dynamic.import_slots(name.to_bytes()) {
+			let previous = unsafe { relocation.value() };
+			let resolved = if relocation.is_unresolved(previous, &load_range) {
+				// The entry still points to the PLT stub that calls into the lazy binding resolver,
+				// which looks in the global scope first and then in the local scope of the object.
+				let version = relocation.symbol().and_then(move |symbol| symbol.version());
+				let lookups = match version {
+					Some(version) => [
+						global_symbol_versioned(name, version.name()),
+						symbols.symbol_versioned(name, version.name()),
+					],
+					None => [global_symbol(name), symbols.symbol(name)],
+				};
+				// Without `dlvsym`, versioned lookups fail, so fall back to the default version.
+				lookups.into_iter()
+					.chain([global_symbol(name), symbols.symbol(name)])
+					.find(move |resolved| !resolved.is_null())
+					.map_or(0, move |resolved| resolved as usize)
+			} else {
+				previous
+			};
+			if resolved != 0 {
+				original.get_or_insert(resolved);
+			}
+			slots.push(ImportSlot {
+				addr: relocation.addr(),
+				kind: relocation.kind(),
+				previous,
+			});
+		}
+		let Some(original) = original else {
+			return Err(Error::NotFound)
+		};
+		let mut hook = Self {
+			slots,
+			original,
+			replacement,
+			relro_pages: object.relro_pages(),
+			restored: false,
+			_symbols: symbols,
+		};
+		for (i, slot) in hook.slots.iter().enumerate() {
+			let written = unsafe { write_slot(slot.addr, hook.relro_pages.as_ref(), |value| value.store(replacement, Ordering::SeqCst)) };
+			if let Err(error) = written {
+				// Only restore the entries that were already written.
+				hook.slots.truncate(i);
+				return Err(error)
+			}
+		}
+		Ok(hook)
+	}
+
+	/// Returns the address that the import was resolved to before it was hooked,
+	/// which can be called to forward to the original implementation.
+	/// 
+	/// Imports that weren't bound yet are looked up in the global scope of the process first,
+	/// and then in the local scope of the object, like the loader does.
+	pub const fn original(&self) -> *const () {
+		self.original as _
+	}
+
+	pub const fn replacement(&self) -> *const () {
+		self.replacement as _
+	}
+
+	/// Returns the GOT entries that were redirected.
+	pub fn slots(&self) -> &[ImportSlot] {
+		&self.slots
+	}
+
+	/// Restores the GOT entries to their previous values,
+	/// returning an error if the protection of their pages couldn't be changed.
+	/// 
+	/// Entries that no longer hold the replacement, such as when they were hooked again by someone else,
+	/// are left as they are.
+	pub fn restore(mut self) -> Result<(), Error> {
+		self.restore_slots()
+	}
+
+	fn restore_slots(&mut self) -> Result<(), Error> {
+		if self.restored {
+			return Ok(())
+		}
+		self.restored = true;
+		let mut result = Ok(());
+		for slot in &self.slots {
+			let restored = unsafe {
+				write_slot(slot.addr, self.relro_pages.as_ref(), |value| {
+					let _ = value.compare_exchange(self.replacement, slot.previous, Ordering::SeqCst, Ordering::SeqCst);
+				})
+			};
+			if result.is_ok() {
+				result = restored;
+			}
+		}
+		result
+	}
+}
+
+impl Drop for ImportHook {
+	fn drop(&mut self) {
+		let _ = self.restore_slots();
+	}
+}
+
+/// Calls `f` with the pointer-sized value at `addr`,
+/// making its page writable for the duration of the call if it is in `relro_pages`.
+/// 
+/// # Safety
+/// `addr` must be an aligned pointer-sized value that is mapped and readable.
+unsafe fn write_slot<F>(addr: usize, relro_pages: Option<&Range<usize>>, f: F) -> Result<(), Error>
+where
+	F: FnOnce(&AtomicUsize),
+{
+	let value = unsafe { &*(addr as *const AtomicUsize) };
+	if relro_pages.is_some_and(move |pages| pages.contains(&addr)) {
+		unsafe { with_protection(addr..addr + size_of::<usize>(), PROT_READ | PROT_WRITE, PROT_READ, move || f(value)) }
+	} else {
+		f(value);
+		Ok(())
+	}
+}
@@ -0,0 +1,390 @@
+use ::core::{
+	ops::Range,
+	slice::from_raw_parts,
+};
+
+use super::{
+	DynamicSection, DynamicSymbol, DynamicTag,
+};
+
+/// Relocation with an addend, equivalent to `ElfW(Rela)`.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ElfRela {
+	pub r_offset: usize,
+	pub r_info: usize,
+	pub r_addend: isize,
+}
+
+/// Relocation without an addend, equivalent to `ElfW(Rel)`.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ElfRel {
+	pub r_offset: usize,
+	pub r_info: usize,
+}
+
+/// Returns the symbol table index in the `r_info` of a relocation (`ELF_R_SYM`).
+pub const fn r_sym(info: usize) -> usize {
+	#[cfg(target_pointer_width = "64")]
+	{ info >> 32 }
+	#[cfg(target_pointer_width = "32")]
+	{ info >> 8 }
+}
+
+/// Returns the type in the `r_info` of a relocation (`ELF_R_TYPE`).
+pub const fn r_type(info: usize) -> u32 {
+	#[cfg(target_pointer_width = "64")]
+	{ (info & 0xffff_ffff) as u32 }
+	#[cfg(target_pointer_width = "32")]
+	{ (info & 0xff) as u32 }
+}
+
+macro_rules! relocation_kinds {
+	{
+		$(
+			#[cfg($($cfg:tt)*)]
+			{
+				$($name:ident = $value:literal,)*
+			}
+		)*
+	} => {
+		impl RelocationKind {
+			/// Returns the kind of the relocation type `raw` of the target architecture.
+			pub const fn from_raw(raw: u32) -> Self {
+				$(
+					#[cfg($($cfg)*)]
+					match raw {
+						$($value => return Self::$name,)*
+						_ => {}
+					}
+				)*
+				Self::Other(raw)
+			}
+		}
+	};
+}
+
+/// Architecture-independent kind of a dynamic relocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RelocationKind {
+	/// No relocation.
+	None,
+	/// Absolute address of a symbol plus the addend, such as `R_X86_64_64`.
+	Absolute,
+	/// GOT entry with the address of a symbol (`GLOB_DAT`).
+	GlobDat,
+	/// PLT GOT entry with the address of a function, which may be lazily bound (`JUMP_SLOT`).
+	JumpSlot,
+	/// Base address of the object plus the addend (`RELATIVE`).
+	Relative,
+	/// Copy of the data of a symbol into the executable (`COPY`).
+	Copy,
+	/// Result of calling the resolver at the base address plus the addend (`IRELATIVE`).
+	IRelative,
+	/// Module ID of the TLS block of a symbol (`DTPMOD`).
+	TlsModule,
+	/// Offset of a symbol in its TLS block (`DTPOFF`).
+	TlsOffset,
+	/// Offset of a symbol from the thread pointer (`TPOFF`).
+	TlsThreadOffset,
+	/// Any other architecture-specific type.
+	Other(u32),
+}
+
+relocation_kinds! {
+	#[cfg(target_arch = "x86_64")]
+	{
+		None = 0,
+		Absolute = 1,
+		Copy = 5,
+		GlobDat = 6,
+		JumpSlot = 7,
+		Relative = 8,
+		TlsModule = 16,
+		TlsOffset = 17,
+		TlsThreadOffset = 18,
+		IRelative = 37,
+	}
+	#[cfg(target_arch = "x86")]
+	{
+		None = 0,
+		Absolute = 1,
+		Copy = 5,
+		GlobDat = 6,
+		JumpSlot = 7,
+		Relative = 8,
+		TlsThreadOffset = 14,
+		TlsModule = 35,
+		TlsOffset = 36,
+		IRelative = 42,
+	}
+	#[cfg(target_arch = "aarch64")]
+	{
+		None = 0,
+		Absolute = 257,
+		Copy = 1024,
+		GlobDat = 1025,
+		JumpSlot = 1026,
+		Relative = 1027,
+		TlsModule = 1028,
+		TlsOffset = 1029,
+		TlsThreadOffset = 1030,
+		IRelative = 1032,
+	}
+	#[cfg(target_arch = "arm")]
+	{
+		None = 0,
+		Absolute = 2,
+		TlsModule = 17,
+		TlsOffset = 18,
+		TlsThreadOffset = 19,
+		Copy = 20,
+		GlobDat = 21,
+		JumpSlot = 22,
+		Relative = 23,
+		IRelative = 160,
+	}
+	#[cfg(target_arch = "riscv64")]
+	{
+		None = 0,
+		Absolute = 2,
+		Relative = 3,
+		Copy = 4,
+		JumpSlot = 5,
+		TlsModule = 7,
+		TlsOffset = 9,
+		TlsThreadOffset = 11,
+		IRelative = 58,
+	}
+}
+
+/// Table of dynamic relocations, either with or without addends.
+#[derive(Debug, Clone, Copy)]
+pub enum RelocationTable<'a> {
+	Rela(&'a [ElfRela]),
+	Rel(&'a [ElfRel]),
+}
+
+impl RelocationTable<'_> {
+	pub const fn len(&self) -> usize {
+		match self {
+			Self::Rela(table) => table.len(),
+			Self::Rel(table) => table.len(),
+		}
+	}
+
+	pub const fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the `r_offset`, `r_info` and addend of the relocation at `index`.
+	fn get(&self, index: usize) -> Option<(usize, usize, Option<isize>)> {
+		match self {
+			Self::Rela(table) => table.get(index).map(move |rela| (rela.r_offset, rela.r_info, Some(rela.r_addend))),
+			Self::Rel(table) => table.get(index).map(move |rel| (rel.r_offset, rel.r_info, None)),
+		}
+	}
+}
+
+impl<'a> DynamicSection<'a> {
+	fn relocation_table(&self, addr: Option<usize>, size: Option<usize>, is_rela: bool) -> Option<RelocationTable<'a>> {
+		let addr = addr?;
+		let size = size?;
+		unsafe {
+			Some(if is_rela {
+				RelocationTable::Rela(from_raw_parts(addr as *const ElfRela, size / size_of::<ElfRela>()))
+			} else {
+				RelocationTable::Rel(from_raw_parts(addr as *const ElfRel, size / size_of::<ElfRel>()))
+			})
+		}
+	}
+
+	/// Returns the PLT relocations (`DT_JMPREL`) of the section.
+	pub fn plt_relocations(&self) -> Option<Relocations<'a>> {
+		let is_rela = self.value(DynamicTag::PltRel)? as isize == super::DT_RELA;
+		let table = self.relocation_table(self.ptr(DynamicTag::JmpRel), self.value(DynamicTag::PltRelSize), is_rela)?;
+		Some(Relocations::new(self.clone(), table))
+	}
+
+	/// Returns the relocations with addends (`DT_RELA`) of the section.
+	pub fn rela_relocations(&self) -> Option<Relocations<'a>> {
+		let table = self.relocation_table(self.ptr(DynamicTag::Rela), self.value(DynamicTag::RelaSize), true)?;
+		Some(Relocations::new(self.clone(), table))
+	}
+
+	/// Returns the relocations without addends (`DT_REL`) of the section.
+	pub fn rel_relocations(&self) -> Option<Relocations<'a>> {
+		let table = self.relocation_table(self.ptr(DynamicTag::Rel), self.value(DynamicTag::RelSize), false)?;
+		Some(Relocations::new(self.clone(), table))
+	}
+
+	/// Returns an iterator over all of the dynamic relocations of the section,
+	/// with the PLT relocations last.
+	/// 
+	/// Packed relative relocations (`DT_RELR`) are not included.
+	pub fn relocations(&self) -> impl Iterator<Item = Relocation<'a>> + use<'a> {
+		let rela = self.rela_relocations();
+		let rel = self.rel_relocations();
+		let plt = self.plt_relocations();
+		// Some linkers include the PLT relocations in the range of `DT_RELA` or `DT_REL` too.
+		let plt_addr = plt.as_ref().map(Relocations::table_addr);
+		let rela = rela.map(move |relocations| relocations.end_before(plt_addr));
+		let rel = rel.map(move |relocations| relocations.end_before(plt_addr));
+		rela.into_iter().flatten()
+			.chain(rel.into_iter().flatten())
+			.chain(plt.into_iter().flatten())
+	}
+
+	/// Returns an iterator over the `GLOB_DAT` and `JUMP_SLOT` relocations for the symbol named `name`,
+	/// which are the GOT entries that calls to and references to the imported symbol go through.
+	pub fn import_slots<'n>(&self, name: &'n [u8]) -> impl Iterator<Item = Relocation<'a>> + use<'a, 'n> {
+		self.relocations().filter(move |relocation| {
+			matches!(relocation.kind(), RelocationKind::JumpSlot | RelocationKind::GlobDat)
+				&& relocation.symbol().is_some_and(move |symbol| symbol.name().to_bytes() == name)
+		})
+	}
+}
+
+/// Iterator over a table of dynamic relocations.
+#[derive(Debug, Clone)]
+pub struct Relocations<'a> {
+	section: DynamicSection<'a>,
+	table: RelocationTable<'a>,
+	index: usize,
+	end: usize,
+}
+
+impl<'a> Relocations<'a> {
+	fn new(section: DynamicSection<'a>, table: RelocationTable<'a>) -> Self {
+		Self {
+			section,
+			end: table.len(),
+			table,
+			index: 0,
+		}
+	}
+
+	/// Returns the raw table of relocations.
+	pub const fn table(&self) -> RelocationTable<'a> {
+		self.table
+	}
+
+	fn table_addr(&self) -> usize {
+		match self.table {
+			RelocationTable::Rela(table) => table.as_ptr() as usize,
+			RelocationTable::Rel(table) => table.as_ptr() as usize,
+		}
+	}
+
+	fn entry_size(&self) -> usize {
+		match self.table {
+			RelocationTable::Rela(..) => size_of::<ElfRela>(),
+			RelocationTable::Rel(..) => size_of::<ElfRel>(),
+		}
+	}
+
+	/// Stops the iteration before the entry at `addr`, if the table contains it.
+	fn end_before(mut self, addr: Option<usize>) -> Self {
+		if let Some(offset) = addr.and_then(|addr| addr.checked_sub(self.table_addr()))
+			&& offset < self.table.len() * self.entry_size()
+		{
+			self.end = offset / self.entry_size();
+		}
+		self
+	}
+}
+
+impl<'a> Iterator for Relocations<'a> {
+	type Item = Relocation<'a>;
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.index >= self.end {
+			return None
+		}
+		let (offset, info, addend) = self.table.get(self.index)?;
+		self.index += 1;
+		let symbol_index = r_sym(info);
+		Some(Relocation {
+			section: self.section.clone(),
+			addr: self.section.base_addr().wrapping_add(offset),
+			kind: RelocationKind::from_raw(r_type(info)),
+			raw_kind: r_type(info),
+			symbol_index,
+			addend,
+		})
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = self.end.saturating_sub(self.index);
+		(remaining, Some(remaining))
+	}
+}
+
+/// Dynamic relocation of an object.
+#[derive(Debug, Clone)]
+pub struct Relocation<'a> {
+	section: DynamicSection<'a>,
+	addr: usize,
+	kind: RelocationKind,
+	raw_kind: u32,
+	symbol_index: usize,
+	addend: Option<isize>,
+}
+
+impl<'a> Relocation<'a> {
+	/// Returns the absolute address that the relocation is applied to,
+	/// such as that of a GOT entry.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	pub const fn kind(&self) -> RelocationKind {
+		self.kind
+	}
+
+	/// Returns the architecture-specific type of the relocation.
+	pub const fn raw_kind(&self) -> u32 {
+		self.raw_kind
+	}
+
+	/// Returns the index of the symbol of the relocation in the symbol table,
+	/// which is `0` if it doesn't have one.
+	pub const fn symbol_index(&self) -> usize {
+		self.symbol_index
+	}
+
+	/// Returns the symbol of the relocation, if it has one.
+	pub fn symbol(&self) -> Option<DynamicSymbol<'a>> {
+		if self.symbol_index == 0 {
+			return None
+		}
+		// SAFETY: Relocations only refer to symbols in the symbol table of their object.
+		unsafe { self.section.symbol(self.symbol_index) }
+	}
+
+	/// Returns `true` if this is a lazily bound [`JumpSlot`](RelocationKind::JumpSlot)
+	/// whose current `value` is the address of a PLT stub in its object at `load_range`.
+	/// 
+	/// Entries that are bound to a definition in the object itself also point into `load_range`,
+	/// so they are told apart by the address of their symbol.
+	pub fn is_unresolved(&self, value: usize, load_range: &Range<usize>) -> bool {
+		self.kind == RelocationKind::JumpSlot
+			&& load_range.contains(&value)
+			&& self.symbol().is_none_or(move |symbol| symbol.addr() != value)
+	}
+
+	/// Returns the addend of the relocation, if it is stored in the relocation itself.
+	pub const fn addend(&self) -> Option<isize> {
+		self.addend
+	}
+
+	/// Returns the value that is currently stored at the [`addr`](Self::addr) of the relocation.
+	/// 
+	/// # Safety
+	/// The relocation must be applied to a word of memory that is still mapped,
+	/// which isn't the case for some kinds, such as [`Copy`](RelocationKind::Copy).
+	pub unsafe fn value(&self) -> usize {
+		unsafe { (self.addr as *const usize).read_unaligned() }
+	}
+}
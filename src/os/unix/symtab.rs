@@ -0,0 +1,320 @@
+use ::core::{
+	ffi::CStr,
+	fmt,
+	iter::Filter,
+	slice::from_raw_parts,
+};
+
+use super::{
+	DynamicSection, DynamicTag, HashTable, SymbolVersion, UnixObject,
+};
+
+/// Section index of undefined symbols.
+pub const SHN_UNDEF: u16 = 0;
+/// Section index of symbols with absolute values.
+pub const SHN_ABS: u16 = 0xfff1;
+
+/// Symbol table entry, equivalent to `ElfW(Sym)`.
+#[cfg(target_pointer_width = "64")]
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ElfSymbol {
+	pub st_name: u32,
+	pub st_info: u8,
+	pub st_other: u8,
+	pub st_shndx: u16,
+	pub st_value: usize,
+	pub st_size: usize,
+}
+
+/// Symbol table entry, equivalent to `ElfW(Sym)`.
+#[cfg(target_pointer_width = "32")]
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ElfSymbol {
+	pub st_name: u32,
+	pub st_value: usize,
+	pub st_size: usize,
+	pub st_info: u8,
+	pub st_other: u8,
+	pub st_shndx: u16,
+}
+
+impl ElfSymbol {
+	pub const fn kind(&self) -> SymbolKind {
+		match self.st_info & 0xf {
+			0 => SymbolKind::NoType,
+			1 => SymbolKind::Object,
+			2 => SymbolKind::Func,
+			3 => SymbolKind::Section,
+			4 => SymbolKind::File,
+			5 => SymbolKind::Common,
+			6 => SymbolKind::Tls,
+			10 => SymbolKind::GnuIfunc,
+			other => SymbolKind::Other(other),
+		}
+	}
+
+	pub const fn binding(&self) -> SymbolBinding {
+		match self.st_info >> 4 {
+			0 => SymbolBinding::Local,
+			1 => SymbolBinding::Global,
+			2 => SymbolBinding::Weak,
+			10 => SymbolBinding::GnuUnique,
+			other => SymbolBinding::Other(other),
+		}
+	}
+
+	pub const fn visibility(&self) -> SymbolVisibility {
+		match self.st_other & 0x3 {
+			0 => SymbolVisibility::Default,
+			1 => SymbolVisibility::Internal,
+			2 => SymbolVisibility::Hidden,
+			_ => SymbolVisibility::Protected,
+		}
+	}
+
+	pub const fn is_defined(&self) -> bool {
+		self.st_shndx != SHN_UNDEF
+	}
+
+	/// Returns `true` if the symbol is defined and can be used by other objects.
+	pub const fn is_exported(&self) -> bool {
+		self.is_defined()
+			&& matches!(self.binding(), SymbolBinding::Global | SymbolBinding::Weak | SymbolBinding::GnuUnique)
+			&& matches!(self.visibility(), SymbolVisibility::Default | SymbolVisibility::Protected)
+	}
+}
+
+/// Type of a symbol (`ELF_ST_TYPE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SymbolKind {
+	NoType,
+	Object,
+	Func,
+	Section,
+	File,
+	Common,
+	Tls,
+	GnuIfunc,
+	Other(u8),
+}
+
+/// Binding of a symbol (`ELF_ST_BIND`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SymbolBinding {
+	Local,
+	Global,
+	Weak,
+	GnuUnique,
+	Other(u8),
+}
+
+/// Visibility of a symbol (`ELF_ST_VISIBILITY`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolVisibility {
+	Default,
+	Internal,
+	Hidden,
+	Protected,
+}
+
+impl UnixObject {
+	/// Returns an iterator over the dynamic symbol table of the object.
+	pub fn dynamic_symbols(&self) -> Option<DynamicSymbols<'_>> {
+		self.dynamic()?.symbols()
+	}
+}
+
+impl<'a> DynamicSection<'a> {
+	/// Returns an iterator over the symbol table (`DT_SYMTAB`) of the section.
+	/// 
+	/// The number of symbols is taken from the symbol hash table,
+	/// so this returns `None` if the object has neither `DT_GNU_HASH` nor `DT_HASH`.
+	pub fn symbols(&self) -> Option<DynamicSymbols<'a>> {
+		let count = HashTable::from_dynamic(self)?.symbol_count();
+		let symtab = self.symtab()?;
+		let versym = self.ptr(DynamicTag::VerSym)
+			.map(move |addr| unsafe { from_raw_parts(addr as *const u16, count) });
+		Some(DynamicSymbols {
+			section: self.clone(),
+			symbols: unsafe { from_raw_parts(symtab as *const ElfSymbol, count) },
+			versym,
+			index: 0,
+		})
+	}
+
+	/// Returns the symbol at `index` in the symbol table of the section.
+	/// 
+	/// # Safety
+	/// `index` must be less than the number of symbols in the table.
+	pub unsafe fn symbol(&self, index: usize) -> Option<DynamicSymbol<'a>> {
+		let symtab = self.symtab()?;
+		unsafe {
+			let symbol = &*(symtab as *const ElfSymbol).add(index);
+			let version = self.ptr(DynamicTag::VerSym).map(move |addr| *(addr as *const u16).add(index));
+			Some(DynamicSymbol::new(self, symbol, index, version))
+		}
+	}
+}
+
+/// Iterator over the entries of a dynamic symbol table.
+/// 
+/// See [`DynamicSection::symbols`].
+#[derive(Debug, Clone)]
+pub struct DynamicSymbols<'a> {
+	section: DynamicSection<'a>,
+	symbols: &'a [ElfSymbol],
+	versym: Option<&'a [u16]>,
+	index: usize,
+}
+
+impl<'a> DynamicSymbols<'a> {
+	/// Returns the number of symbols in the table, including the null symbol at index 0.
+	pub const fn len(&self) -> usize {
+		self.symbols.len()
+	}
+
+	pub const fn is_empty(&self) -> bool {
+		self.symbols.is_empty()
+	}
+
+	/// Returns an iterator over only the symbols that are [exported](ElfSymbol::is_exported).
+	pub fn exported(self) -> Filter<Self, fn(&DynamicSymbol<'a>) -> bool> {
+		let is_exported: fn(&DynamicSymbol<'a>) -> bool = |symbol| symbol.raw().is_exported();
+		self.filter(is_exported)
+	}
+}
+
+impl<'a> Iterator for DynamicSymbols<'a> {
+	type Item = DynamicSymbol<'a>;
+	fn next(&mut self) -> Option<Self::Item> {
+		let symbol = self.symbols.get(self.index)?;
+		let version = self.versym.and_then(|versym| versym.get(self.index).copied());
+		let item = DynamicSymbol::new(&self.section, symbol, self.index, version);
+		self.index += 1;
+		Some(item)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = self.symbols.len() - self.index;
+		(remaining, Some(remaining))
+	}
+}
+
+impl ExactSizeIterator for DynamicSymbols<'_> {}
+
+/// Entry of a dynamic symbol table.
+#[derive(Clone, Copy)]
+pub struct DynamicSymbol<'a> {
+	name: &'a CStr,
+	symbol: &'a ElfSymbol,
+	index: usize,
+	addr: usize,
+	version_index: Option<u16>,
+	version_name: Option<&'a CStr>,
+}
+
+impl<'a> DynamicSymbol<'a> {
+	fn new(section: &DynamicSection<'a>, symbol: &'a ElfSymbol, index: usize, version: Option<u16>) -> Self {
+		let addr = match symbol.st_shndx {
+			SHN_UNDEF => 0,
+			SHN_ABS => symbol.st_value,
+			_ => section.base_addr().wrapping_add(symbol.st_value),
+		};
+		Self {
+			name: section.string(symbol.st_name as usize).unwrap_or(c""),
+			symbol,
+			index,
+			addr,
+			version_index: version,
+			version_name: version.and_then(|index| section.version_name(index)),
+		}
+	}
+
+	/// Returns the raw symbol table entry.
+	pub const fn raw(&self) -> &'a ElfSymbol {
+		self.symbol
+	}
+
+	pub const fn name(&self) -> &'a CStr {
+		self.name
+	}
+
+	/// Returns the demangled name of the symbol,
+	/// or the name itself if it isn't mangled.
+	#[cfg(feature = "demangle")]
+	pub fn demangled_name(&self) -> ::std::borrow::Cow<'a, str> {
+		crate::demangle::demangle_lossy(self.name.to_bytes())
+	}
+
+	/// Returns the index of the symbol in the symbol table.
+	pub const fn index(&self) -> usize {
+		self.index
+	}
+
+	/// Returns the absolute address of the symbol,
+	/// or `0` if the symbol is undefined.
+	/// 
+	/// For [`Tls`](SymbolKind::Tls) symbols, this is not meaningful.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	pub const fn size(&self) -> usize {
+		self.symbol.st_size
+	}
+
+	pub const fn kind(&self) -> SymbolKind {
+		self.symbol.kind()
+	}
+
+	pub const fn binding(&self) -> SymbolBinding {
+		self.symbol.binding()
+	}
+
+	pub const fn visibility(&self) -> SymbolVisibility {
+		self.symbol.visibility()
+	}
+
+	/// Returns the index of the section that the symbol is defined in,
+	/// or [`SHN_UNDEF`] if it is undefined.
+	pub const fn section_index(&self) -> u16 {
+		self.symbol.st_shndx
+	}
+
+	pub const fn is_defined(&self) -> bool {
+		self.symbol.is_defined()
+	}
+
+	/// Returns the raw entry of the symbol in the version table (`DT_VERSYM`),
+	/// if the object has one.
+	pub const fn version_index(&self) -> Option<u16> {
+		self.version_index
+	}
+
+	/// Returns the version of the symbol, if it has one.
+	pub fn version(&self) -> Option<SymbolVersion<'a>> {
+		Some(SymbolVersion {
+			name: self.version_name?,
+			index: self.version_index?,
+		})
+	}
+}
+
+impl fmt::Debug for DynamicSymbol<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("DynamicSymbol")
+			.field("name", &self.name)
+			.field("addr", &format_args!("{:#x}", self.addr))
+			.field("size", &self.size())
+			.field("kind", &self.kind())
+			.field("binding", &self.binding())
+			.field("visibility", &self.visibility())
+			.field("section_index", &self.section_index())
+			.field("version", &self.version())
+			.finish()
+	}
+}
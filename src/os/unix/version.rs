@@ -0,0 +1,385 @@
+use ::core::{
+	ffi::CStr,
+	fmt,
+};
+
+use super::{
+	DynamicSection, DynamicSymbol, DynamicTag, HashTable,
+	VERSYM_HIDDEN,
+};
+
+/// `DT_VERSYM` index of symbols that are local to the object.
+pub const VER_NDX_LOCAL: u16 = 0;
+/// `DT_VERSYM` index of symbols that are global and unversioned.
+pub const VER_NDX_GLOBAL: u16 = 1;
+/// Flag of the version definition that names the object itself.
+pub const VER_FLG_BASE: u16 = 0x1;
+/// Flag of versions that are weak references.
+pub const VER_FLG_WEAK: u16 = 0x2;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct ElfVerdef {
+	vd_version: u16,
+	vd_flags: u16,
+	vd_ndx: u16,
+	vd_cnt: u16,
+	vd_hash: u32,
+	vd_aux: u32,
+	vd_next: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct ElfVerdaux {
+	vda_name: u32,
+	vda_next: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct ElfVerneed {
+	vn_version: u16,
+	vn_cnt: u16,
+	vn_file: u32,
+	vn_aux: u32,
+	vn_next: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct ElfVernaux {
+	vna_hash: u32,
+	vna_flags: u16,
+	vna_other: u16,
+	vna_name: u32,
+	vna_next: u32,
+}
+
+impl<'a> DynamicSection<'a> {
+	/// Returns an iterator over the versions that the object defines (`DT_VERDEF`).
+	pub fn version_definitions(&self) -> VersionDefinitions<'a> {
+		VersionDefinitions {
+			section: self.clone(),
+			next: self.ptr(DynamicTag::VerDef).unwrap_or(0),
+			remaining: self.value(DynamicTag::VerDefNum).unwrap_or(0),
+		}
+	}
+
+	/// Returns an iterator over the versions that the object requires from other objects (`DT_VERNEED`).
+	pub fn version_requirements(&self) -> VersionRequirements<'a> {
+		VersionRequirements {
+			section: self.clone(),
+			next: self.ptr(DynamicTag::VerNeed).unwrap_or(0),
+			remaining: self.value(DynamicTag::VerNeedNum).unwrap_or(0),
+		}
+	}
+
+	/// Returns the name of the version with the given `DT_VERSYM` index,
+	/// ignoring the [`VERSYM_HIDDEN`] bit.
+	pub fn version_name(&self, index: u16) -> Option<&'a CStr> {
+		let index = index & !VERSYM_HIDDEN;
+		if index == VER_NDX_LOCAL || index == VER_NDX_GLOBAL {
+			return None
+		}
+		if let Some(definition) = self.version_definitions().find(move |definition| definition.index() == index) {
+			return Some(definition.name())
+		}
+		self.version_requirements()
+			.flat_map(move |requirement| requirement.versions())
+			.find(move |version| version.index() == index)
+			.map(move |version| version.name())
+	}
+
+	/// Finds the exported symbol named `name` with the given `version` in the symbol hash table of the section.
+	/// 
+	/// Unlike [`lookup`](Self::lookup), this also finds non-default versions of symbols.
+	pub fn lookup_versioned(&self, name: &CStr, version: &CStr) -> Option<DynamicSymbol<'a>> {
+		let table = HashTable::from_dynamic(self)?;
+		let name = name.to_bytes();
+		let mut found = None;
+		table.find(name, |index| {
+			// SAFETY: The hash table only yields indices of symbols in the table.
+			let Some(symbol) = (unsafe { self.symbol(index) }) else {
+				return false
+			};
+			let is_match = symbol.raw().is_exported()
+				&& symbol.name().to_bytes() == name
+				&& symbol.version().is_some_and(move |symbol_version| symbol_version.name() == version);
+			if is_match {
+				found = Some(symbol);
+			}
+			is_match
+		});
+		found
+	}
+}
+
+/// Version of a symbol, as given by its `DT_VERSYM` entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymbolVersion<'a> {
+	pub(crate) name: &'a CStr,
+	pub(crate) index: u16,
+}
+
+impl<'a> SymbolVersion<'a> {
+	pub const fn name(&self) -> &'a CStr {
+		self.name
+	}
+
+	/// Returns the `DT_VERSYM` index of the version, without the [`VERSYM_HIDDEN`] bit.
+	pub const fn index(&self) -> u16 {
+		self.index & !VERSYM_HIDDEN
+	}
+
+	/// Returns `true` if this is not the default version of the symbol,
+	/// meaning that it is not found by ordinary lookups.
+	pub const fn is_hidden(&self) -> bool {
+		self.index & VERSYM_HIDDEN != 0
+	}
+}
+
+impl fmt::Display for SymbolVersion<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let separator = if self.is_hidden() { "@" } else { "@@" };
+		write!(f, "{separator}{}", self.name.to_string_lossy())
+	}
+}
+
+/// Iterator over the version definitions of an object.
+/// 
+/// See [`DynamicSection::version_definitions`].
+#[derive(Debug, Clone)]
+pub struct VersionDefinitions<'a> {
+	section: DynamicSection<'a>,
+	next: usize,
+	remaining: usize,
+}
+
+impl<'a> Iterator for VersionDefinitions<'a> {
+	type Item = VersionDefinition<'a>;
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.next == 0 || self.remaining == 0 {
+			return None
+		}
+		let addr = self.next;
+		let verdef = unsafe { &*(addr as *const ElfVerdef) };
+		self.remaining -= 1;
+		self.next = if verdef.vd_next != 0 { addr.wrapping_add(verdef.vd_next as usize) } else { 0 };
+		Some(VersionDefinition {
+			section: self.section.clone(),
+			addr,
+			verdef,
+		})
+	}
+}
+
+/// Version that is defined by an object.
+#[derive(Clone)]
+pub struct VersionDefinition<'a> {
+	section: DynamicSection<'a>,
+	addr: usize,
+	verdef: &'a ElfVerdef,
+}
+
+impl<'a> VersionDefinition<'a> {
+	/// Returns the `DT_VERSYM` index that refers to this version.
+	pub const fn index(&self) -> u16 {
+		self.verdef.vd_ndx
+	}
+
+	/// Returns the flags of the version, such as [`VER_FLG_BASE`].
+	pub const fn flags(&self) -> u16 {
+		self.verdef.vd_flags
+	}
+
+	/// Returns `true` if this definition names the object itself rather than a version.
+	pub const fn is_base(&self) -> bool {
+		self.flags() & VER_FLG_BASE != 0
+	}
+
+	/// Returns the ELF hash of the version's name.
+	pub const fn hash(&self) -> u32 {
+		self.verdef.vd_hash
+	}
+
+	/// Returns an iterator over the name of the version, followed by the names of the versions that it inherits from.
+	pub fn names(&self) -> VersionNames<'a> {
+		VersionNames {
+			section: self.section.clone(),
+			next: if self.verdef.vd_cnt != 0 { self.addr.wrapping_add(self.verdef.vd_aux as usize) } else { 0 },
+			remaining: self.verdef.vd_cnt as usize,
+		}
+	}
+
+	pub fn name(&self) -> &'a CStr {
+		self.names().next().unwrap_or(c"")
+	}
+
+	/// Returns an iterator over the names of the versions that this version inherits from.
+	pub fn parents(&self) -> VersionNames<'a> {
+		let mut names = self.names();
+		names.next();
+		names
+	}
+}
+
+impl fmt::Debug for VersionDefinition<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("VersionDefinition")
+			.field("index", &self.index())
+			.field("flags", &self.flags())
+			.field("name", &self.name())
+			.finish()
+	}
+}
+
+/// Iterator over the names of a [`VersionDefinition`].
+#[derive(Debug, Clone)]
+pub struct VersionNames<'a> {
+	section: DynamicSection<'a>,
+	next: usize,
+	remaining: usize,
+}
+
+impl<'a> Iterator for VersionNames<'a> {
+	type Item = &'a CStr;
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.next == 0 || self.remaining == 0 {
+			return None
+		}
+		let verdaux = unsafe { &*(self.next as *const ElfVerdaux) };
+		self.remaining -= 1;
+		self.next = if verdaux.vda_next != 0 { self.next.wrapping_add(verdaux.vda_next as usize) } else { 0 };
+		self.section.string(verdaux.vda_name as usize)
+	}
+}
+
+/// Iterator over the version requirements of an object.
+/// 
+/// See [`DynamicSection::version_requirements`].
+#[derive(Debug, Clone)]
+pub struct VersionRequirements<'a> {
+	section: DynamicSection<'a>,
+	next: usize,
+	remaining: usize,
+}
+
+impl<'a> Iterator for VersionRequirements<'a> {
+	type Item = VersionRequirement<'a>;
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.next == 0 || self.remaining == 0 {
+			return None
+		}
+		let addr = self.next;
+		let verneed = unsafe { &*(addr as *const ElfVerneed) };
+		self.remaining -= 1;
+		self.next = if verneed.vn_next != 0 { addr.wrapping_add(verneed.vn_next as usize) } else { 0 };
+		Some(VersionRequirement {
+			section: self.section.clone(),
+			addr,
+			verneed,
+		})
+	}
+}
+
+/// Versions that an object requires from one other object.
+#[derive(Clone)]
+pub struct VersionRequirement<'a> {
+	section: DynamicSection<'a>,
+	addr: usize,
+	verneed: &'a ElfVerneed,
+}
+
+impl<'a> VersionRequirement<'a> {
+	/// Returns the name of the object that the versions are required from.
+	pub fn file(&self) -> &'a CStr {
+		self.section.string(self.verneed.vn_file as usize).unwrap_or(c"")
+	}
+
+	/// Returns an iterator over the versions that are required from the [`file`](Self::file).
+	pub fn versions(&self) -> RequiredVersions<'a> {
+		RequiredVersions {
+			section: self.section.clone(),
+			next: if self.verneed.vn_cnt != 0 { self.addr.wrapping_add(self.verneed.vn_aux as usize) } else { 0 },
+			remaining: self.verneed.vn_cnt as usize,
+		}
+	}
+}
+
+impl fmt::Debug for VersionRequirement<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("VersionRequirement")
+			.field("file", &self.file())
+			.field("versions", &self.versions())
+			.finish()
+	}
+}
+
+/// Iterator over the versions of a [`VersionRequirement`].
+#[derive(Clone)]
+pub struct RequiredVersions<'a> {
+	section: DynamicSection<'a>,
+	next: usize,
+	remaining: usize,
+}
+
+impl<'a> Iterator for RequiredVersions<'a> {
+	type Item = RequiredVersion<'a>;
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.next == 0 || self.remaining == 0 {
+			return None
+		}
+		let vernaux = unsafe { &*(self.next as *const ElfVernaux) };
+		self.remaining -= 1;
+		self.next = if vernaux.vna_next != 0 { self.next.wrapping_add(vernaux.vna_next as usize) } else { 0 };
+		Some(RequiredVersion {
+			name: self.section.string(vernaux.vna_name as usize).unwrap_or(c""),
+			vernaux,
+		})
+	}
+}
+
+impl fmt::Debug for RequiredVersions<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_list().entries(self.clone()).finish()
+	}
+}
+
+/// Version that an object requires from another object.
+#[derive(Clone, Copy)]
+pub struct RequiredVersion<'a> {
+	name: &'a CStr,
+	vernaux: &'a ElfVernaux,
+}
+
+impl<'a> RequiredVersion<'a> {
+	pub const fn name(&self) -> &'a CStr {
+		self.name
+	}
+
+	/// Returns the `DT_VERSYM` index that refers to this version.
+	pub const fn index(&self) -> u16 {
+		self.vernaux.vna_other
+	}
+
+	/// Returns the flags of the version, such as [`VER_FLG_WEAK`].
+	pub const fn flags(&self) -> u16 {
+		self.vernaux.vna_flags
+	}
+
+	/// Returns the ELF hash of the version's name.
+	pub const fn hash(&self) -> u32 {
+		self.vernaux.vna_hash
+	}
+}
+
+impl fmt::Debug for RequiredVersion<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("RequiredVersion")
+			.field("name", &self.name)
+			.field("index", &self.index())
+			.field("flags", &self.flags())
+			.finish()
+	}
+}
@@ -1,9 +1,12 @@
 use ::core::{
 	ffi::CStr,
+	marker::PhantomData,
 	mem::{
 		MaybeUninit, size_of_val,
 	},
+	ops::Range,
 };
+use ::std::ffi::CString;
 use ::winapi::{
 	shared::minwindef::{
 		DWORD, HMODULE, FARPROC,
@@ -21,16 +24,35 @@ use ::winapi::{
 	}
 };
 
-use crate::map::*;
+use crate::{
+	map::*,
+	SegmentKind,
+};
 
 use super::lifetime_wrapper;
 
 mod library;
 pub use library::*;
+mod owned;
+pub(crate) use owned::*;
 mod tlhelp32;
 pub use tlhelp32::*;
 
 pub use ::std::io::Error;
+impl super::ErrorImpl for Error {
+	fn kind(&self) -> crate::ErrorKind {
+		/// `ERROR_MOD_NOT_FOUND`.
+		const MOD_NOT_FOUND: i32 = 126;
+		/// `ERROR_PROC_NOT_FOUND`.
+		const PROC_NOT_FOUND: i32 = 127;
+
+		match self.raw_os_error() {
+			Some(MOD_NOT_FOUND | PROC_NOT_FOUND) => crate::ErrorKind::NotFound,
+			_ if self.kind() == ::std::io::ErrorKind::NotFound => crate::ErrorKind::NotFound,
+			_ => crate::ErrorKind::Os,
+		}
+	}
+}
 
 lifetime_wrapper! {
 	pub(crate) struct Segment for Module;
@@ -38,6 +60,16 @@ lifetime_wrapper! {
 
 pub(crate) type Segments<'a> = ::core::iter::Once<Segment<'a>>;
 
+/// Iterator over the functions of a module, which are not available on Windows yet.
+#[derive(Debug, Clone)]
+pub(crate) struct Functions<'a>(PhantomData<&'a ()>);
+impl Iterator for Functions<'_> {
+	type Item = Range<usize>;
+	fn next(&mut self) -> Option<Self::Item> {
+		None
+	}
+}
+
 lifetime_wrapper! {
 	pub(crate) struct ModuleName for OwnedModuleName;
 }
@@ -113,34 +145,54 @@ impl super::ObjectImpl for Object<'_> {
 		});
 		::core::iter::once(segment)
 	}
-	fn symbols(&self) -> Symbols {
-		Symbols
+	fn symbols(&self) -> Result<Symbols, Error> {
+		Ok(Symbols)
 	}
 	fn symbol(&self, symbols: &Symbols, name: &CStr) -> *mut () {
 		let _ = symbols;
 		self.inner.symbol(name) as _
 	}
-	fn library(&self, symbols: Symbols) -> Library {
+	fn lookup_symbol(&self, name: &CStr) -> *mut () {
+		self.inner.symbol(name) as _
+	}
+	fn function_containing(&self, addr: usize) -> Option<Range<usize>> {
+		let _ = addr;
+		None
+	}
+	fn functions(&self) -> Functions<'_> {
+		Functions(PhantomData)
+	}
+	fn library(&self, symbols: Symbols) -> Result<Library, Error> {
 		let _ = symbols;
-		match Library::from_module(&self.inner) {
-			Ok(lib) => lib,
-			Err(error) => {
-				panic!("couldn't create owned Windows module:\n{error:#?}");
-			}
-		}
+		Library::from_module(&self.inner)
 	}
 }
 
 impl super::SegmentImpl for Segment<'_> {
+	fn kind(&self) -> SegmentKind {
+		SegmentKind::Load
+	}
 	fn flags(&self) -> SegmentFlags {
 		SegmentFlags
 	}
+	fn base_addr(&self) -> usize {
+		self.inner.handle as _
+	}
+	fn file_offset(&self) -> usize {
+		0
+	}
+	fn file_size(&self) -> usize {
+		self.inner.size as _
+	}
 	fn virtual_addr(&self) -> usize {
 		0
 	}
 	fn size(&self) -> usize {
 		self.inner.size as _
 	}
+	fn align(&self) -> usize {
+		1
+	}
 }
 
 #[derive(Clone, Copy)]
@@ -196,6 +248,13 @@ impl super::ObjectsImpl for Objects {
 		}
 		Ok(())
 	}
+	fn nearest_symbol(&self, addr: usize) -> Option<(CString, usize)> {
+		let _ = addr;
+		None
+	}
+	fn generation(&self) -> Option<(u64, u64)> {
+		None
+	}
 }
 
 impl Objects {
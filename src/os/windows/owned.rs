@@ -0,0 +1,47 @@
+use ::core::ffi::CStr;
+use ::std::ffi::CString;
+use ::winapi::shared::minwindef::{
+	DWORD, HMODULE,
+};
+
+use super::{
+	Module, Object,
+	Segment, Segments,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct OwnedObject {
+	name: CString,
+	handle: HMODULE,
+	size: DWORD,
+}
+
+// SAFETY: `HMODULE` is only used as an address, and is never dereferenced mutably.
+unsafe impl Send for OwnedObject {}
+unsafe impl Sync for OwnedObject {}
+
+impl super::super::OwnedObjectImpl for OwnedObject {
+	fn new(name: &CStr, object: &Object<'_>) -> Self {
+		Self {
+			name: CString::from(name),
+			handle: object.inner.handle,
+			size: object.inner.size,
+		}
+	}
+	fn name(&self) -> &CStr {
+		&self.name
+	}
+	fn object(&self) -> Object<'_> {
+		Object::new(Module {
+			handle: self.handle,
+			size: self.size,
+		})
+	}
+	fn segments(&self) -> Segments<'_> {
+		let segment = Segment::new(Module {
+			handle: self.handle,
+			size: self.size,
+		});
+		::core::iter::once(segment)
+	}
+}
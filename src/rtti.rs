@@ -0,0 +1,524 @@
+use ::core::{
+	ffi::CStr,
+	fmt,
+	marker::PhantomData,
+	ops::Range,
+};
+
+use ::std::ffi::CString;
+
+use crate::{
+	os::unix::{
+		read_memory, Error as UnixError,
+	},
+	scan::{is_code_segment, is_data_segment},
+	snapshot::OwnedObject,
+	xref::pointer_xrefs,
+	Error, Object, Objects,
+};
+
+/// Mangled names of the vtables of the `std::type_info` subclasses that describe classes.
+const CLASS_TYPE_INFO_VTABLE: &CStr = c"_ZTVN10__cxxabiv117__class_type_infoE";
+const SI_CLASS_TYPE_INFO_VTABLE: &CStr = c"_ZTVN10__cxxabiv120__si_class_type_infoE";
+const VMI_CLASS_TYPE_INFO_VTABLE: &CStr = c"_ZTVN10__cxxabiv121__vmi_class_type_infoE";
+
+/// Offset of the address point of a vtable from its start,
+/// which is past the offset-to-top and the `type_info` pointer.
+pub const VTABLE_ADDRESS_POINT: usize = 2 * size_of::<usize>();
+
+/// Largest distance that an offset-to-top is assumed to have,
+/// which is used to tell vtables apart from other references to `type_info` objects.
+const MAX_OFFSET_TO_TOP: isize = 1 << 24;
+
+/// Mangles a class name, such as `foo::Bar` or `std::runtime_error`,
+/// into the form that is used in its `typeinfo name` string (`N3foo3BarE`, `St13runtime_error`).
+/// 
+/// Returns `None` for names that can't be mangled this simply, such as templates.
+pub fn mangle_class_name(name: &str) -> Option<Vec<u8>> {
+	let mut parts: Vec<&str> = name.split("::").collect();
+	let is_valid = |part: &&str| {
+		!part.is_empty() && part.bytes().all(move |c| c.is_ascii_alphanumeric() || c == b'_')
+	};
+	if !parts.iter().all(is_valid) {
+		return None
+	}
+	let mut mangled = Vec::new();
+	let is_std = parts.len() > 1 && parts[0] == "std";
+	if is_std {
+		parts.remove(0);
+	}
+	let is_nested = parts.len() > 1;
+	if is_nested {
+		mangled.push(b'N');
+	}
+	if is_std {
+		mangled.extend_from_slice(b"St");
+	}
+	for part in parts {
+		mangled.extend_from_slice(part.len().to_string().as_bytes());
+		mangled.extend_from_slice(part.as_bytes());
+	}
+	if is_nested {
+		mangled.push(b'E');
+	}
+	Some(mangled)
+}
+
+/// Demangles the `typeinfo name` of a class, such as `N3foo3BarE`, into `foo::Bar`.
+/// 
+/// This is the inverse of [`mangle_class_name`],
+/// so it returns `None` for names that use other parts of the mangling grammar, such as templates.
+pub fn demangle_class_name(mangled: &[u8]) -> Option<String> {
+	let (mut rest, is_nested) = match mangled {
+		[b'N', inner @ .., b'E'] => (inner, true),
+		_ => (mangled, false),
+	};
+	let mut parts = Vec::new();
+	if let Some(inner) = rest.strip_prefix(b"St") {
+		parts.push("std");
+		rest = inner;
+	}
+	while !rest.is_empty() {
+		let digits = rest.iter().position(move |c| !c.is_ascii_digit()).unwrap_or(rest.len());
+		let len: usize = ::core::str::from_utf8(&rest[..digits]).ok()?.parse().ok()?;
+		let part = rest.get(digits..digits + len)?;
+		parts.push(::core::str::from_utf8(part).ok()?);
+		rest = &rest[digits + len..];
+	}
+	let std_parts = usize::from(mangled.starts_with(b"St") || mangled.starts_with(b"NSt"));
+	let other_parts = parts.len() - std_parts;
+	if other_parts == 0 || (!is_nested && other_parts != 1) {
+		return None
+	}
+	Some(parts.join("::"))
+}
+
+/// Kind of a [`TypeInfo`] of a class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TypeInfoKind {
+	/// Class without bases (`__class_type_info`).
+	Class,
+	/// Class with a single public non-virtual base at offset zero (`__si_class_type_info`).
+	SingleInheritance,
+	/// Class with any other bases (`__vmi_class_type_info`).
+	VirtualMultipleInheritance,
+}
+
+/// Addresses of the vtables of the `std::type_info` subclasses in the process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub(crate) struct TypeInfoVtables {
+	class: Option<usize>,
+	si_class: Option<usize>,
+	vmi_class: Option<usize>,
+}
+
+impl TypeInfoVtables {
+	/// Finds the vtables in the C++ runtime library of the process.
+	pub(crate) fn find(objects: &Objects) -> Self {
+		let lookup = |name: &CStr| {
+			objects.find_map(move |_, object| {
+				let addr = object.lookup_symbol(name) as usize;
+				(addr != 0).then_some(addr + VTABLE_ADDRESS_POINT)
+			}).ok().flatten()
+		};
+		Self {
+			class: lookup(CLASS_TYPE_INFO_VTABLE),
+			si_class: lookup(SI_CLASS_TYPE_INFO_VTABLE),
+			vmi_class: lookup(VMI_CLASS_TYPE_INFO_VTABLE),
+		}
+	}
+
+	pub(crate) fn kind_of(&self, vtable: usize) -> Option<TypeInfoKind> {
+		if Some(vtable) == self.class {
+			Some(TypeInfoKind::Class)
+		} else if Some(vtable) == self.si_class {
+			Some(TypeInfoKind::SingleInheritance)
+		} else if Some(vtable) == self.vmi_class {
+			Some(TypeInfoKind::VirtualMultipleInheritance)
+		} else {
+			None
+		}
+	}
+}
+
+/// `std::type_info` object of a class.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypeInfo<'a> {
+	addr: usize,
+	kind: TypeInfoKind,
+	/// Used to tell the kinds of the bases of the class apart.
+	vtables: TypeInfoVtables,
+	_object: PhantomData<&'a ()>,
+}
+
+impl<'a> TypeInfo<'a> {
+	/// Looks up the vtables of the `type_info` classes in the loaded objects once,
+	/// which the `TypeInfo`s of [`bases`](Self::bases) then share.
+	/// 
+	/// # Safety
+	/// `addr` must point to a `type_info` object of the given `kind`
+	/// that stays valid for the lifetime of the returned value.
+	pub unsafe fn from_addr(addr: usize, kind: TypeInfoKind) -> Self {
+		unsafe { Self::with_vtables(addr, kind, TypeInfoVtables::find(&Objects::new())) }
+	}
+
+	/// # Safety
+	/// See [`from_addr`](Self::from_addr).
+	pub(crate) const unsafe fn with_vtables(addr: usize, kind: TypeInfoKind, vtables: TypeInfoVtables) -> Self {
+		Self {
+			addr,
+			kind,
+			vtables,
+			_object: PhantomData,
+		}
+	}
+
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	pub const fn kind(&self) -> TypeInfoKind {
+		self.kind
+	}
+
+	fn word(&self, index: usize) -> usize {
+		unsafe { *(self.addr as *const usize).add(index) }
+	}
+
+	/// Returns the mangled name of the class, such as `N3foo3BarE`.
+	pub fn name(&self) -> &CStr {
+		let name = self.word(1) as *const u8;
+		// Some compilers mark names that must be compared by address with a leading `*`.
+		let name = unsafe { if *name == b'*' { name.add(1) } else { name } };
+		unsafe { CStr::from_ptr(name as *const _) }
+	}
+
+	/// Returns the direct bases of the class.
+	/// 
+	/// Bases whose `type_info` isn't of a known class kind are skipped.
+	pub fn bases(&self) -> Vec<BaseClass<'a>> {
+		let new_base = |type_info: usize, offset_flags: isize| {
+			let kind = self.vtables.kind_of(unsafe { *(type_info as *const usize) })?;
+			Some(BaseClass {
+				type_info: unsafe { Self::with_vtables(type_info, kind, self.vtables) },
+				offset_flags,
+			})
+		};
+		match self.kind {
+			TypeInfoKind::Class => Vec::new(),
+			// A single base is public, non-virtual, and at offset zero.
+			TypeInfoKind::SingleInheritance => new_base(self.word(2), BaseClass::PUBLIC).into_iter().collect(),
+			TypeInfoKind::VirtualMultipleInheritance => {
+				// The vtable and name pointers are followed by the flags and the number of bases.
+				let counts = unsafe { *((self.addr + 2 * size_of::<usize>()) as *const [u32; 2]) };
+				let bases = (self.addr + 2 * size_of::<usize>() + 2 * size_of::<u32>()) as *const [usize; 2];
+				(0..counts[1] as usize)
+					.filter_map(move |index| {
+						let [type_info, offset_flags] = unsafe { *bases.add(index) };
+						new_base(type_info, offset_flags as isize)
+					})
+					.collect()
+			}
+		}
+	}
+}
+
+impl fmt::Debug for TypeInfo<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("TypeInfo")
+			.field("addr", &format_args!("{:#x}", self.addr))
+			.field("kind", &self.kind)
+			.field("name", &self.name())
+			.finish()
+	}
+}
+
+/// Direct base of a class, as described by its [`TypeInfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BaseClass<'a> {
+	type_info: TypeInfo<'a>,
+	offset_flags: isize,
+}
+
+impl<'a> BaseClass<'a> {
+	const VIRTUAL: isize = 0x1;
+	const PUBLIC: isize = 0x2;
+
+	pub const fn type_info(&self) -> &TypeInfo<'a> {
+		&self.type_info
+	}
+
+	/// Returns the offset of the base in the class,
+	/// or of its offset in the vtable for [virtual](Self::is_virtual) bases.
+	pub const fn offset(&self) -> isize {
+		self.offset_flags >> 8
+	}
+
+	pub const fn is_virtual(&self) -> bool {
+		self.offset_flags & Self::VIRTUAL != 0
+	}
+
+	pub const fn is_public(&self) -> bool {
+		self.offset_flags & Self::PUBLIC != 0
+	}
+}
+
+/// Vtable of a class that was found through its [`TypeInfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Vtable<'a> {
+	addr: usize,
+	offset_to_top: isize,
+	slots: usize,
+	_object: PhantomData<&'a ()>,
+}
+
+impl<'a> Vtable<'a> {
+	/// Returns the address point of the vtable,
+	/// which is what the vtable pointers of objects point to.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	/// Returns the address of the start of the vtable, which is its offset-to-top.
+	pub const fn start(&self) -> usize {
+		self.addr - VTABLE_ADDRESS_POINT
+	}
+
+	/// Returns the offset from the subobject that uses the vtable to the start of the whole object.
+	/// 
+	/// This is zero for the primary vtable of a class,
+	/// and negative for the vtables of its secondary bases.
+	pub const fn offset_to_top(&self) -> isize {
+		self.offset_to_top
+	}
+
+	/// Returns `true` if this is the primary vtable of the class.
+	pub const fn is_primary(&self) -> bool {
+		self.offset_to_top == 0
+	}
+
+	/// Returns the number of virtual function slots in the vtable.
+	/// 
+	/// This is found by counting the consecutive entries that point to code,
+	/// so it may be too small if the object has entries that don't point to code,
+	/// or too large if the next vtable in memory starts immediately after.
+	pub const fn slots(&self) -> usize {
+		self.slots
+	}
+
+	/// Returns the virtual function slots of the vtable.
+	pub fn functions(&self) -> &'a [usize] {
+		unsafe { ::core::slice::from_raw_parts(self.addr as *const usize, self.slots) }
+	}
+}
+
+/// Class that was found with [`Object::find_class`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassInfo<'a> {
+	type_info: TypeInfo<'a>,
+	bases: Vec<BaseClass<'a>>,
+	vtables: Vec<Vtable<'a>>,
+}
+
+impl<'a> ClassInfo<'a> {
+	pub const fn type_info(&self) -> &TypeInfo<'a> {
+		&self.type_info
+	}
+
+	/// Returns the direct bases of the class.
+	pub fn bases(&self) -> &[BaseClass<'a>] {
+		&self.bases
+	}
+
+	/// Returns the vtables of the class in the object, with the primary one first.
+	pub fn vtables(&self) -> &[Vtable<'a>] {
+		&self.vtables
+	}
+
+	/// Returns the primary vtable of the class.
+	pub fn primary_vtable(&self) -> Option<&Vtable<'a>> {
+		self.vtables.first().filter(move |vtable| vtable.is_primary())
+	}
+}
+
+/// Returns the ranges of addresses of the executable segments of all loaded objects.
+pub(crate) fn code_ranges(objects: &Objects) -> Vec<Range<usize>> {
+	let mut ranges = Vec::new();
+	let _ = objects.for_each(|_, object| {
+		ranges.extend(object.segments().filter(is_code_segment).map(move |segment| segment.addr_range()));
+	});
+	ranges
+}
+
+/// Finds the class whose `typeinfo name` string is `mangled`
+/// in the read-only data of `object`, along with its vtables.
+pub(crate) fn find_class<'a>(object: &'a Object<'_>, mangled: &[u8]) -> Option<ClassInfo<'a>> {
+	let objects = Objects::new();
+	let type_info_vtables = TypeInfoVtables::find(&objects);
+	let name = object.find_string(mangled)?;
+	let segments: Vec<_> = object.segments().map(move |segment| segment.addr_range()).collect();
+
+	// The name is the second field of the `type_info` object.
+	let type_info = pointer_xrefs(object, name).into_iter().find_map(|xref| {
+		let segment = &segments[xref.segment_index()];
+		let addr = xref.addr().checked_sub(size_of::<usize>()).filter(|addr| *addr >= segment.start)?;
+		let kind = type_info_vtables.kind_of(unsafe { *(addr as *const usize) })?;
+		Some(unsafe { TypeInfo::with_vtables(addr, kind, type_info_vtables) })
+	})?;
+
+	let code = code_ranges(&objects);
+	let is_code = move |addr: usize| code.iter().any(move |range| range.contains(&addr));
+	let mut vtables: Vec<_> = pointer_xrefs(object, type_info.addr()).into_iter().filter_map(|xref| {
+		// The `type_info` pointer of a vtable is preceded by its offset-to-top,
+		// while in the `type_info` objects of derived classes it's preceded by other pointers or flags.
+		let segment = &segments[xref.segment_index()];
+		let start = xref.addr().checked_sub(size_of::<usize>()).filter(|start| *start >= segment.start)?;
+		let offset_to_top = unsafe { *(start as *const isize) };
+		if !(-MAX_OFFSET_TO_TOP..=0).contains(&offset_to_top) || offset_to_top % size_of::<usize>() as isize != 0 {
+			return None
+		}
+		let addr = xref.addr() + size_of::<usize>();
+		let slots = (addr..segment.end).step_by(size_of::<usize>())
+			.take_while(|&slot| is_code(unsafe { *(slot as *const usize) }))
+			.count();
+		if slots == 0 && offset_to_top == 0 {
+			return None
+		}
+		Some(Vtable {
+			addr,
+			offset_to_top,
+			slots,
+			_object: PhantomData,
+		})
+	}).collect();
+	vtables.sort_by_key(move |vtable| (-vtable.offset_to_top, vtable.addr));
+
+	Some(ClassInfo {
+		bases: type_info.bases(),
+		type_info,
+		vtables,
+	})
+}
+
+/// Dynamic type of a polymorphic C++ object,
+/// as returned by [`Objects::dynamic_type`].
+#[derive(Debug, Clone)]
+pub struct DynamicType {
+	object: OwnedObject,
+	addr: usize,
+	vtable: usize,
+	offset_to_top: isize,
+	type_info: usize,
+	kind: TypeInfoKind,
+	mangled_name: CString,
+}
+
+impl DynamicType {
+	/// Returns the object that contains the vtable of the C++ object, which usually defines its class.
+	pub const fn object(&self) -> &OwnedObject {
+		&self.object
+	}
+
+	/// Returns the address of the vtable that the C++ object points to.
+	pub const fn vtable(&self) -> usize {
+		self.vtable
+	}
+
+	/// Returns the offset from the C++ object to the start of its most derived object.
+	pub const fn offset_to_top(&self) -> isize {
+		self.offset_to_top
+	}
+
+	/// Returns the address of the most derived object that contains the C++ object.
+	pub const fn top_addr(&self) -> usize {
+		self.addr.wrapping_add_signed(self.offset_to_top)
+	}
+
+	/// Returns the address of the `type_info` of the most derived class.
+	pub const fn type_info_addr(&self) -> usize {
+		self.type_info
+	}
+
+	pub const fn type_info_kind(&self) -> TypeInfoKind {
+		self.kind
+	}
+
+	/// Returns the mangled name of the most derived class, such as `N3foo3BarE`.
+	pub fn mangled_name(&self) -> &CStr {
+		&self.mangled_name
+	}
+
+	/// Returns the demangled name of the most derived class, such as `foo::Bar`,
+	/// or the mangled one if it can't be demangled.
+	/// 
+	/// Without the `demangle` feature, only the names that [`demangle_class_name`] supports are demangled.
+	pub fn name(&self) -> String {
+		let mangled = self.mangled_name.to_bytes();
+		if let Some(name) = demangle_class_name(mangled) {
+			return name
+		}
+		#[cfg(feature = "demangle")]
+		if let Some(name) = crate::demangle::demangle_type_name(mangled) {
+			return name
+		}
+		self.mangled_name.to_string_lossy().into_owned()
+	}
+}
+
+/// Returns the range of addresses of the [data segment](is_data_segment) of any loaded object that contains `addr`.
+fn data_segment_containing(objects: &Objects, addr: usize) -> Option<Range<usize>> {
+	objects.find_map(move |_, object| {
+		object.segments()
+			.find(move |segment| is_data_segment(segment) && segment.contains(addr))
+			.map(move |segment| segment.addr_range())
+	}).ok().flatten()
+}
+
+fn read_word(addr: usize) -> Result<usize, Error> {
+	let mut bytes = [0; size_of::<usize>()];
+	match read_memory(addr, &mut bytes) {
+		Ok(()) => Ok(usize::from_ne_bytes(bytes)),
+		Err(error) => Err(Error(error)),
+	}
+}
+
+/// Finds the dynamic type of the polymorphic C++ object at `addr`.
+pub(crate) fn dynamic_type(objects: &Objects, addr: usize) -> Result<DynamicType, Error> {
+	let not_found = move || Error(UnixError::NotFound);
+	let malformed = move |what| Error(UnixError::Malformed(what));
+
+	let vtable = read_word(addr)?;
+	let info = objects.object_containing(vtable)?.ok_or_else(not_found)?;
+	let segment = info.segment();
+	// Vtables are in relocated read-only data, which is never executable.
+	if !is_data_segment(&segment) || segment.flags().has_x() || vtable - segment.addr() < VTABLE_ADDRESS_POINT {
+		return Err(not_found())
+	}
+	// SAFETY: Both values are in the same segment as the address point of the vtable.
+	let offset_to_top = unsafe { *((vtable - VTABLE_ADDRESS_POINT) as *const isize) };
+	let type_info = unsafe { *((vtable - size_of::<usize>()) as *const usize) };
+
+	let type_info_segment = data_segment_containing(objects, type_info).ok_or_else(move || malformed("vtable has no valid type_info"))?;
+	if type_info_segment.end - type_info < 2 * size_of::<usize>() {
+		return Err(malformed("vtable has no valid type_info"))
+	}
+	let type_info_vtables = TypeInfoVtables::find(objects);
+	let kind = type_info_vtables.kind_of(unsafe { *(type_info as *const usize) }).ok_or_else(move || malformed("type_info is not of a class"))?;
+
+	let name = unsafe { *((type_info + size_of::<usize>()) as *const usize) };
+	let name_segment = data_segment_containing(objects, name).ok_or_else(move || malformed("type_info has no valid name"))?;
+	// SAFETY: The name is in a readable segment, and is read only up to the end of it.
+	let name_bytes = unsafe { ::core::slice::from_raw_parts(name as *const u8, name_segment.end - name) };
+	let name_bytes = name_bytes.strip_prefix(b"*").unwrap_or(name_bytes);
+	let len = ::memchr::memchr(0, name_bytes).ok_or_else(move || malformed("type_info has no valid name"))?;
+
+	Ok(DynamicType {
+		object: info.object().clone(),
+		addr,
+		vtable,
+		offset_to_top,
+		type_info,
+		kind,
+		mangled_name: CString::new(&name_bytes[..len]).map_err(move |_| malformed("type_info has no valid name"))?,
+	})
+}
@@ -0,0 +1,612 @@
+use ::core::{
+	fmt,
+	iter::FusedIterator,
+	slice::from_raw_parts,
+};
+use ::aho_corasick::AhoCorasick;
+use ::memchr::memmem::Finder;
+use ::std::{
+	borrow::Cow,
+	thread,
+};
+
+use crate::{
+	Object, Segment, Segments,
+};
+
+/// Byte signature with wildcards, which can be searched for in the code of an object.
+/// 
+/// Every byte of the pattern has a mask.
+/// A byte of data matches the pattern if `data & mask == bytes & mask`,
+/// so a mask of `0xff` requires an exact match, and a mask of `0x00` is a full wildcard.
+/// Masks of `0xf0` and `0x0f` can be used to match only the high or low nibble of a byte.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Pattern<'a> {
+	bytes: Cow<'a, [u8]>,
+	mask: Cow<'a, [u8]>,
+}
+
+impl<'a> Pattern<'a> {
+	/// Creates a pattern out of `bytes` and their `mask`.
+	/// 
+	/// # Panics
+	/// Panics if `bytes` and `mask` aren't of the same length.
+	pub const fn new(bytes: &'a [u8], mask: &'a [u8]) -> Self {
+		assert!(bytes.len() == mask.len(), "pattern bytes and mask should be of the same length");
+		Self {
+			bytes: Cow::Borrowed(bytes),
+			mask: Cow::Borrowed(mask),
+		}
+	}
+
+	/// Creates a pattern out of `bytes` and a code-style `mask`,
+	/// such as `"xx??x"`, where `?` marks wildcard bytes.
+	pub fn with_code_mask(bytes: &[u8], mask: &str) -> Result<Pattern<'static>, PatternError> {
+		if bytes.len() != mask.len() {
+			return Err(PatternError::LengthMismatch)
+		}
+		let mask = mask.bytes().enumerate()
+			.map(move |(index, c)| match c {
+				b'x' | b'X' => Ok(0xff),
+				b'?' => Ok(0x00),
+				_ => Err(PatternError::InvalidToken { index }),
+			})
+			.collect::<Result<Vec<u8>, _>>()?;
+		Ok(Pattern {
+			bytes: Cow::Owned(bytes.iter().zip(&mask).map(move |(&byte, &mask)| byte & mask).collect()),
+			mask: Cow::Owned(mask),
+		})
+	}
+
+	/// Parses an IDA-style signature, such as `"48 8B ?? ?? 89"`.
+	/// 
+	/// Each whitespace-separated token is a byte of two hexadecimal digits,
+	/// where either digit can be replaced by `?` to make it a wildcard.
+	/// A single `?` is also accepted as a full wildcard byte.
+	pub fn parse(s: &str) -> Result<Pattern<'static>, PatternError> {
+		let mut bytes = Vec::new();
+		let mut mask = Vec::new();
+		for (index, token) in s.split_ascii_whitespace().enumerate() {
+			let (byte, byte_mask) = parse_token(token.as_bytes()).ok_or(PatternError::InvalidToken { index })?;
+			bytes.push(byte);
+			mask.push(byte_mask);
+		}
+		if bytes.is_empty() {
+			return Err(PatternError::Empty)
+		}
+		Ok(Pattern {
+			bytes: Cow::Owned(bytes),
+			mask: Cow::Owned(mask),
+		})
+	}
+
+	/// Returns an owned version of the pattern.
+	pub fn into_owned(self) -> Pattern<'static> {
+		Pattern {
+			bytes: Cow::Owned(self.bytes.into_owned()),
+			mask: Cow::Owned(self.mask.into_owned()),
+		}
+	}
+
+	pub fn bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+
+	pub fn mask(&self) -> &[u8] {
+		&self.mask
+	}
+
+	/// Returns the number of bytes in the pattern.
+	pub fn len(&self) -> usize {
+		self.bytes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.bytes.is_empty()
+	}
+
+	/// Returns `true` if the start of `data` matches the pattern.
+	pub fn matches_at(&self, data: &[u8]) -> bool {
+		data.len() >= self.len()
+			&& data.iter().zip(self.bytes.iter().zip(self.mask.iter()))
+				.all(move |(&data, (&byte, &mask))| data & mask == byte & mask)
+	}
+
+	/// Returns the offset and length of the longest run of exactly matched bytes in the pattern.
+	/// 
+	/// This run is searched for first, and the rest of the pattern is only checked where it is found.
+	pub fn anchor(&self) -> (usize, usize) {
+		let mut best = (0, 0);
+		let mut start = 0;
+		for (index, &mask) in self.mask.iter().chain(Some(&0)).enumerate() {
+			if mask != 0xff {
+				if index - start > best.1 {
+					best = (start, index - start);
+				}
+				start = index + 1;
+			}
+		}
+		best
+	}
+
+	/// Returns an iterator over the offsets of all matches of the pattern in `data`,
+	/// including overlapping ones.
+	pub fn find_iter<'d>(&'d self, data: &'d [u8]) -> Matches<'d> {
+		let (anchor_offset, anchor_len) = self.anchor();
+		Matches {
+			pattern: self,
+			finder: Finder::new(&self.bytes[anchor_offset..anchor_offset + anchor_len]),
+			anchor_offset,
+			data,
+			pos: 0,
+		}
+	}
+
+	/// Returns the offset of the first match of the pattern in `data`.
+	pub fn find(&self, data: &[u8]) -> Option<usize> {
+		self.find_iter(data).next()
+	}
+}
+
+impl fmt::Debug for Pattern<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("Pattern").field(&format_args!("{self}")).finish()
+	}
+}
+
+impl fmt::Display for Pattern<'_> {
+	/// Formats the pattern as an IDA-style signature.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+		for (index, (&byte, &mask)) in self.bytes.iter().zip(self.mask.iter()).enumerate() {
+			if index != 0 {
+				f.write_str(" ")?;
+			}
+			for shift in [4, 0] {
+				if (mask >> shift) & 0xf == 0xf {
+					write!(f, "{}", DIGITS[((byte >> shift) & 0xf) as usize] as char)?;
+				} else {
+					f.write_str("?")?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+impl ::core::str::FromStr for Pattern<'static> {
+	type Err = PatternError;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::parse(s)
+	}
+}
+
+/// Error that can occur when creating a [`Pattern`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum PatternError {
+	#[error("pattern is empty")]
+	Empty,
+	#[error("invalid pattern token at index {index}")]
+	InvalidToken {
+		index: usize,
+	},
+	#[error("pattern bytes and mask have different lengths")]
+	LengthMismatch,
+	#[error("pattern set is too large to build a searcher for")]
+	SetTooLarge,
+}
+
+/// Parses a single token of an IDA-style signature into a byte and its mask.
+const fn parse_token(token: &[u8]) -> Option<(u8, u8)> {
+	match token {
+		[b'?'] | [b'?', b'?'] => Some((0, 0)),
+		&[high, low] => {
+			let (high, high_mask) = match parse_nibble(high) {
+				Some(nibble) => nibble,
+				None => return None,
+			};
+			let (low, low_mask) = match parse_nibble(low) {
+				Some(nibble) => nibble,
+				None => return None,
+			};
+			Some(((high << 4) | low, (high_mask << 4) | low_mask))
+		}
+		_ => None,
+	}
+}
+
+/// Parses a hexadecimal digit or `?` into a nibble and its mask.
+const fn parse_nibble(c: u8) -> Option<(u8, u8)> {
+	match c {
+		b'0'..=b'9' => Some((c - b'0', 0xf)),
+		b'a'..=b'f' => Some((c - b'a' + 10, 0xf)),
+		b'A'..=b'F' => Some((c - b'A' + 10, 0xf)),
+		b'?' => Some((0, 0)),
+		_ => None,
+	}
+}
+
+/// Returns the start and end of the first token of an IDA-style signature at or after `pos`.
+const fn next_token(s: &[u8], mut pos: usize) -> Option<(usize, usize)> {
+	while pos < s.len() && s[pos].is_ascii_whitespace() {
+		pos += 1;
+	}
+	if pos == s.len() {
+		return None
+	}
+	let start = pos;
+	while pos < s.len() && !s[pos].is_ascii_whitespace() {
+		pos += 1;
+	}
+	Some((start, pos))
+}
+
+/// Returns the number of bytes in an IDA-style signature, panicking if it's invalid.
+/// 
+/// Used by [`pattern!`](crate::pattern).
+#[doc(hidden)]
+pub const fn signature_len(s: &str) -> usize {
+	let s = s.as_bytes();
+	let mut len = 0;
+	let mut pos = 0;
+	while let Some((start, end)) = next_token(s, pos) {
+		let (token, _) = s.split_at(end);
+		let (_, token) = token.split_at(start);
+		if parse_token(token).is_none() {
+			panic!("invalid token in pattern signature")
+		}
+		len += 1;
+		pos = end;
+	}
+	if len == 0 {
+		panic!("pattern signature is empty")
+	}
+	len
+}
+
+/// Parses an IDA-style signature of `N` bytes into its bytes and mask, panicking if it's invalid.
+/// 
+/// Used by [`pattern!`](crate::pattern).
+#[doc(hidden)]
+pub const fn parse_signature<const N: usize>(s: &str) -> ([u8; N], [u8; N]) {
+	let s = s.as_bytes();
+	let mut bytes = [0; N];
+	let mut mask = [0; N];
+	let mut index = 0;
+	let mut pos = 0;
+	while let Some((start, end)) = next_token(s, pos) {
+		let (token, _) = s.split_at(end);
+		let (_, token) = token.split_at(start);
+		let Some((byte, byte_mask)) = parse_token(token) else {
+			panic!("invalid token in pattern signature")
+		};
+		if index == N {
+			panic!("pattern signature is longer than expected")
+		}
+		bytes[index] = byte;
+		mask[index] = byte_mask;
+		index += 1;
+		pos = end;
+	}
+	if index != N {
+		panic!("pattern signature is shorter than expected")
+	}
+	(bytes, mask)
+}
+
+/// Creates a [`Pattern<'static>`](Pattern) out of an IDA-style signature,
+/// checking its syntax at compile time.
+/// 
+/// See [`Pattern::parse`] for the syntax.
+/// Since the result is a constant, it can be used to initialize `static`s.
+/// 
+/// ```
+/// use loaded::{pattern, scan::Pattern};
+/// static SIGNATURE: Pattern<'static> = pattern!("48 8B ?? ?? 89 4?");
+/// assert_eq!(SIGNATURE.mask(), &[0xff, 0xff, 0, 0, 0xff, 0xf0]);
+/// ```
+/// 
+/// Invalid signatures fail to compile:
+/// ```compile_fail
+/// static SIGNATURE: loaded::scan::Pattern<'static> = loaded::pattern!("48 8G");
+/// ```
+#[macro_export]
+macro_rules! pattern {
+	($signature:expr $(,)?) => {{
+		const SIGNATURE: &::core::primitive::str = $signature;
+		const LEN: ::core::primitive::usize = $crate::scan::signature_len(SIGNATURE);
+		const PARSED: ([::core::primitive::u8; LEN], [::core::primitive::u8; LEN]) = $crate::scan::parse_signature::<LEN>(SIGNATURE);
+		const PATTERN: $crate::scan::Pattern<'static> = $crate::scan::Pattern::new(&PARSED.0, &PARSED.1);
+		PATTERN
+	}};
+}
+
+/// Iterator over the offsets of the matches of a [`Pattern`] in a slice.
+/// 
+/// See [`Pattern::find_iter`].
+#[derive(Debug, Clone)]
+pub struct Matches<'d> {
+	pattern: &'d Pattern<'d>,
+	finder: Finder<'d>,
+	anchor_offset: usize,
+	data: &'d [u8],
+	pos: usize,
+}
+
+impl Iterator for Matches<'_> {
+	type Item = usize;
+	fn next(&mut self) -> Option<Self::Item> {
+		let last = self.data.len().checked_sub(self.pattern.len())?;
+		while self.pos <= last {
+			let start = if self.finder.needle().is_empty() {
+				self.pos
+			} else {
+				let haystack = &self.data[self.pos + self.anchor_offset..];
+				let Some(found) = self.finder.find(haystack) else {
+					self.pos = usize::MAX;
+					return None
+				};
+				self.pos + found
+			};
+			if start > last {
+				self.pos = start;
+				return None
+			}
+			self.pos = start + 1;
+			if self.pattern.matches_at(&self.data[start..]) {
+				return Some(start)
+			}
+		}
+		None
+	}
+}
+
+impl FusedIterator for Matches<'_> {}
+
+/// Returns the memory of a loaded `segment`.
+/// 
+/// # Safety
+/// The whole segment must be mapped and readable.
+pub(crate) unsafe fn segment_bytes<'a>(segment: &Segment<'_>) -> &'a [u8] {
+	unsafe { from_raw_parts(segment.addr() as *const u8, segment.size()) }
+}
+
+/// Returns `true` if `segment` should be scanned for code signatures.
+pub(crate) fn is_code_segment(segment: &Segment<'_>) -> bool {
+	segment.is_loadable() && segment.flags().is_rx() && segment.size() != 0
+}
+
+/// Returns `true` if `segment` may hold read-only data, such as strings and `type_info` objects.
+/// 
+/// Executable segments are included, since older linkers put read-only data in the same segment as code.
+pub(crate) fn is_data_segment(segment: &Segment<'_>) -> bool {
+	segment.is_loadable() && segment.flags().has_r()
+}
+
+/// Iterator over the absolute addresses of the matches of a [`Pattern`]
+/// in the executable segments of an object.
+/// 
+/// See [`Object::scan`](crate::Object::scan).
+pub struct Scan<'a, 'p> {
+	pattern: &'p Pattern<'p>,
+	segments: Segments<'a>,
+	current: Option<(usize, Matches<'p>)>,
+}
+
+impl<'a, 'p> Scan<'a, 'p> {
+	pub(crate) fn new(pattern: &'p Pattern<'p>, segments: Segments<'a>) -> Self {
+		Self {
+			pattern,
+			segments,
+			current: None,
+		}
+	}
+}
+
+impl Iterator for Scan<'_, '_> {
+	type Item = usize;
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some((addr, matches)) = &mut self.current
+				&& let Some(offset) = matches.next()
+			{
+				return Some(*addr + offset)
+			}
+			let segment = self.segments.find(is_code_segment)?;
+			// SAFETY: Loadable segments are mapped for as long as their object is loaded,
+			// and executable ones are also readable.
+			let data = unsafe { segment_bytes(&segment) };
+			self.current = Some((segment.addr(), self.pattern.find_iter(data)));
+		}
+	}
+}
+
+/// Set of [`Pattern`]s that can be searched for together in a single pass over the data.
+/// 
+/// The [anchors](Pattern::anchor) of all of the patterns are searched for at once with Aho-Corasick,
+/// and each pattern is only checked where its anchor is found.
+/// Patterns are identified by their index in the set.
+#[derive(Debug, Clone)]
+pub struct PatternSet<'p> {
+	patterns: Vec<Pattern<'p>>,
+	anchor_offsets: Vec<usize>,
+	searcher: AhoCorasick,
+	/// Index of the pattern for each anchor that is searched for.
+	anchored: Vec<usize>,
+	/// Indices of the patterns that don't have an anchor.
+	unanchored: Vec<usize>,
+}
+
+impl<'p> PatternSet<'p> {
+	pub fn new<I: IntoIterator<Item = Pattern<'p>>>(patterns: I) -> Result<Self, PatternError> {
+		let patterns: Vec<_> = patterns.into_iter().collect();
+		let mut anchor_offsets = Vec::with_capacity(patterns.len());
+		let mut anchors = Vec::with_capacity(patterns.len());
+		let mut anchored = Vec::with_capacity(patterns.len());
+		let mut unanchored = Vec::new();
+		for (id, pattern) in patterns.iter().enumerate() {
+			if pattern.is_empty() {
+				return Err(PatternError::Empty)
+			}
+			let (offset, len) = pattern.anchor();
+			anchor_offsets.push(offset);
+			if len != 0 {
+				anchors.push(&pattern.bytes()[offset..offset + len]);
+				anchored.push(id);
+			} else {
+				unanchored.push(id);
+			}
+		}
+		let searcher = AhoCorasick::new(anchors).map_err(move |_| PatternError::SetTooLarge)?;
+		Ok(Self {
+			patterns,
+			anchor_offsets,
+			searcher,
+			anchored,
+			unanchored,
+		})
+	}
+
+	/// Returns the number of patterns in the set.
+	pub fn len(&self) -> usize {
+		self.patterns.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.patterns.is_empty()
+	}
+
+	/// Returns the pattern with the given `id`.
+	pub fn get(&self, id: usize) -> Option<&Pattern<'p>> {
+		self.patterns.get(id)
+	}
+
+	/// Searches for all of the patterns in `data`,
+	/// adding `base_addr` to the offsets of the matches.
+	pub fn scan_bytes(&self, data: &[u8], base_addr: usize) -> SetMatches {
+		let mut matches = SetMatches::new(self.len());
+		self.scan_into(data, base_addr, &mut matches);
+		matches.sort();
+		matches
+	}
+
+	/// Searches for all of the patterns in the readable and executable segments of `object`.
+	pub fn scan(&self, object: &Object<'_>) -> SetMatches {
+		let mut matches = SetMatches::new(self.len());
+		for segment in object.segments().filter(is_code_segment) {
+			// SAFETY: See `Scan::next`.
+			let data = unsafe { segment_bytes(&segment) };
+			self.scan_into(data, segment.addr(), &mut matches);
+		}
+		matches.sort();
+		matches
+	}
+
+	/// Like [`scan`](Self::scan), but searches each segment on its own thread.
+	/// 
+	/// This is only faster for objects with several large executable segments.
+	pub fn scan_parallel(&self, object: &Object<'_>) -> SetMatches {
+		let segments: Vec<_> = object.segments()
+			.filter(is_code_segment)
+			// SAFETY: See `Scan::next`.
+			.map(move |segment| (unsafe { segment_bytes(&segment) }, segment.addr()))
+			.collect();
+		let mut matches = SetMatches::new(self.len());
+		thread::scope(|scope| {
+			let handles: Vec<_> = segments.into_iter()
+				.map(|(data, addr)| scope.spawn(move || self.scan_bytes(data, addr)))
+				.collect();
+			for handle in handles {
+				match handle.join() {
+					Ok(segment_matches) => matches.extend(segment_matches),
+					Err(payload) => ::std::panic::resume_unwind(payload),
+				}
+			}
+		});
+		matches.sort();
+		matches
+	}
+
+	fn scan_into(&self, data: &[u8], base_addr: usize, matches: &mut SetMatches) {
+		for found in self.searcher.find_overlapping_iter(data) {
+			let id = self.anchored[found.pattern().as_usize()];
+			let Some(start) = found.start().checked_sub(self.anchor_offsets[id]) else {
+				continue
+			};
+			if self.patterns[id].matches_at(&data[start..]) {
+				matches.matches[id].push(base_addr.wrapping_add(start));
+			}
+		}
+		for &id in &self.unanchored {
+			let addrs = self.patterns[id].find_iter(data).map(move |offset| base_addr.wrapping_add(offset));
+			matches.matches[id].extend(addrs);
+		}
+	}
+}
+
+/// Addresses of the matches of each pattern in a [`PatternSet`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetMatches {
+	matches: Vec<Vec<usize>>,
+}
+
+impl SetMatches {
+	fn new(len: usize) -> Self {
+		Self {
+			matches: vec![Vec::new(); len],
+		}
+	}
+
+	fn extend(&mut self, other: Self) {
+		for (matches, other) in self.matches.iter_mut().zip(other.matches) {
+			matches.extend(other);
+		}
+	}
+
+	fn sort(&mut self) {
+		for matches in &mut self.matches {
+			matches.sort_unstable();
+		}
+	}
+
+	/// Returns the number of patterns that were searched for.
+	pub fn len(&self) -> usize {
+		self.matches.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.matches.is_empty()
+	}
+
+	/// Returns the sorted addresses of all matches of the pattern with the given `id`.
+	pub fn get(&self, id: usize) -> &[usize] {
+		self.matches.get(id).map(Vec::as_slice).unwrap_or_default()
+	}
+
+	/// Returns the lowest address of a match of the pattern with the given `id`.
+	pub fn first(&self, id: usize) -> Option<usize> {
+		self.get(id).first().copied()
+	}
+
+	/// Returns the address of the match of the pattern with the given `id`
+	/// if it was matched exactly once.
+	pub fn unique(&self, id: usize) -> Option<usize> {
+		match self.get(id) {
+			&[addr] => Some(addr),
+			_ => None,
+		}
+	}
+
+	/// Returns an iterator over the IDs of the patterns that were matched more than once.
+	pub fn ambiguous(&self) -> impl Iterator<Item = usize> + '_ {
+		self.matches.iter().enumerate()
+			.filter_map(move |(id, matches)| (matches.len() > 1).then_some(id))
+	}
+
+	/// Returns an iterator over the IDs of the patterns that weren't matched at all.
+	pub fn missing(&self) -> impl Iterator<Item = usize> + '_ {
+		self.matches.iter().enumerate()
+			.filter_map(move |(id, matches)| matches.is_empty().then_some(id))
+	}
+}
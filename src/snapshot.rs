@@ -0,0 +1,152 @@
+use ::core::{
+	ffi::CStr,
+	fmt,
+	ops::Index,
+	slice::Iter,
+};
+use ::std::vec::IntoIter;
+
+use crate::{
+	os::*,
+	util::check_lib_name,
+	Error, Object, Objects, Segments,
+};
+
+/// Loaded object whose name and segment information have been copied into owned memory.
+/// 
+/// Unlike [`Object`], values of this type can outlive the query that produced them,
+/// and can be sent across threads.
+/// Note that the object itself may still be unloaded after the copy was made,
+/// in which case addresses derived from it are no longer valid.
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct OwnedObject(imp::OwnedObject);
+impl OwnedObject {
+	pub(crate) fn new(name: &CStr, object: &Object<'_>) -> Self {
+		Self(OwnedObjectImpl::new(name, &object.0))
+	}
+
+	/// Returns the name that the object was loaded with.
+	pub fn name(&self) -> &CStr {
+		OwnedObjectImpl::name(&self.0)
+	}
+
+	/// Returns a borrowed view of the object.
+	pub fn object(&self) -> Object<'_> {
+		Object(OwnedObjectImpl::object(&self.0))
+	}
+
+	pub fn is_main_program(&self) -> bool {
+		self.object().is_main_program()
+	}
+
+	pub fn base_addr(&self) -> usize {
+		self.object().base_addr()
+	}
+
+	pub fn segments(&self) -> Segments<'_> {
+		Segments(OwnedObjectImpl::segments(&self.0))
+	}
+}
+
+impl fmt::Debug for OwnedObject {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("OwnedObject")
+			.field("base_addr", &format_args!("{:x}", self.base_addr()))
+			.field("name", &self.name())
+			.finish()
+	}
+}
+
+/// Owned copy of all of the objects that were loaded at one point in time.
+/// 
+/// The snapshot is an ordinary collection,
+/// so it can be iterated over, indexed and sorted without any callbacks.
+#[derive(Debug, Clone, Default)]
+pub struct ObjectSnapshot {
+	objects: Vec<OwnedObject>,
+}
+
+impl ObjectSnapshot {
+	/// Takes a snapshot of all of the currently loaded objects.
+	pub fn new() -> Result<Self, Error> {
+		Self::from_objects(&Objects::new())
+	}
+
+	pub(crate) fn from_objects(objects: &Objects) -> Result<Self, Error> {
+		let mut snapshot = Vec::new();
+		objects.for_each(|name, object| snapshot.push(OwnedObject::new(name, &object)))?;
+		Ok(Self {
+			objects: snapshot,
+		})
+	}
+
+	pub fn len(&self) -> usize {
+		self.objects.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.objects.is_empty()
+	}
+
+	pub fn get(&self, index: usize) -> Option<&OwnedObject> {
+		self.objects.get(index)
+	}
+
+	pub fn as_slice(&self) -> &[OwnedObject] {
+		&self.objects
+	}
+
+	pub fn iter(&self) -> Iter<'_, OwnedObject> {
+		self.objects.iter()
+	}
+
+	/// Returns the first object in the snapshot whose name matches `name`.
+	/// 
+	/// Names are matched in the same way as in [`Objects::map_by_name`].
+	pub fn find_by_name(&self, name: &CStr) -> Option<&OwnedObject> {
+		let name = name.to_bytes();
+		self.objects.iter().find(move |object| check_lib_name(object.name().to_bytes(), name))
+	}
+
+	/// Returns the first object in the snapshot with a loadable segment that contains `addr`.
+	pub fn object_containing(&self, addr: usize) -> Option<&OwnedObject> {
+		self.objects.iter().find(move |object| {
+			object.segments().any(move |segment| segment.is_loadable() && segment.contains(addr))
+		})
+	}
+
+	/// Sorts the objects in the snapshot by their base address, in ascending order.
+	pub fn sort_by_base_addr(&mut self) {
+		self.objects.sort_by_key(OwnedObject::base_addr);
+	}
+}
+
+impl Index<usize> for ObjectSnapshot {
+	type Output = OwnedObject;
+	fn index(&self, index: usize) -> &Self::Output {
+		&self.objects[index]
+	}
+}
+
+impl IntoIterator for ObjectSnapshot {
+	type Item = OwnedObject;
+	type IntoIter = IntoIter<OwnedObject>;
+	fn into_iter(self) -> Self::IntoIter {
+		self.objects.into_iter()
+	}
+}
+
+impl<'a> IntoIterator for &'a ObjectSnapshot {
+	type Item = &'a OwnedObject;
+	type IntoIter = Iter<'a, OwnedObject>;
+	fn into_iter(self) -> Self::IntoIter {
+		self.objects.iter()
+	}
+}
+
+impl From<ObjectSnapshot> for Vec<OwnedObject> {
+	fn from(snapshot: ObjectSnapshot) -> Self {
+		snapshot.objects
+	}
+}
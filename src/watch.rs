@@ -0,0 +1,187 @@
+use ::core::{
+	ffi::CStr,
+	fmt,
+	time::Duration,
+};
+use ::std::{
+	thread::sleep,
+	time::Instant,
+};
+
+use crate::{
+	os::*,
+	snapshot::OwnedObject,
+	util::check_lib_name,
+	Error, Objects,
+};
+
+type Callback = Box<dyn FnMut(&OwnedObject) + Send>;
+
+/// Structure that keeps track of loaded objects and reports when they are loaded or unloaded.
+/// 
+/// # Platform usage
+/// On Unix, the `dlpi_adds` and `dlpi_subs` counters of `dl_phdr_info`
+/// are used to skip comparing the loaded objects when nothing has changed.
+/// Other platforms compare the loaded objects on every [`poll`](Self::poll).
+pub struct ObjectWatcher {
+	objects: Objects,
+	known: Vec<OwnedObject>,
+	generation: Option<(u64, u64)>,
+	on_added: Vec<Callback>,
+	on_removed: Vec<Callback>,
+	poll_interval: Duration,
+}
+
+impl ObjectWatcher {
+	/// Default interval between polls in [`wait_for`](Self::wait_for).
+	pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+	/// Returns a new watcher that considers all currently loaded objects to be known.
+	pub fn new() -> Result<Self, Error> {
+		let objects = Objects::new();
+		let generation = ObjectsImpl::generation(&objects.0);
+		let known = objects.snapshot()?.into();
+		Ok(Self {
+			objects,
+			known,
+			generation,
+			on_added: Vec::new(),
+			on_removed: Vec::new(),
+			poll_interval: Self::DEFAULT_POLL_INTERVAL,
+		})
+	}
+
+	/// Returns the objects that were loaded as of the last [`poll`](Self::poll).
+	pub fn objects(&self) -> &[OwnedObject] {
+		&self.known
+	}
+
+	/// Registers `f` to be called with every object that is loaded.
+	pub fn on_added<F>(&mut self, f: F) -> &mut Self
+	where
+		F: 'static + FnMut(&OwnedObject) + Send,
+	{
+		self.on_added.push(Box::new(f));
+		self
+	}
+
+	/// Registers `f` to be called with every object that is unloaded.
+	pub fn on_removed<F>(&mut self, f: F) -> &mut Self
+	where
+		F: 'static + FnMut(&OwnedObject) + Send,
+	{
+		self.on_removed.push(Box::new(f));
+		self
+	}
+
+	/// Sets the interval between polls in [`wait_for`](Self::wait_for).
+	pub fn set_poll_interval(&mut self, interval: Duration) -> &mut Self {
+		self.poll_interval = interval;
+		self
+	}
+
+	/// Returns `true` if the loaded objects may have changed since the last [`poll`](Self::poll).
+	/// 
+	/// This is always `true` on platforms without load and unload counters.
+	pub fn has_changed(&self) -> bool {
+		match (self.generation, ObjectsImpl::generation(&self.objects.0)) {
+			(Some(old), Some(new)) => old != new,
+			_ => true,
+		}
+	}
+
+	/// Compares the loaded objects with the ones that were known before,
+	/// calls the registered callbacks and returns the differences.
+	pub fn poll(&mut self) -> Result<ObjectChanges, Error> {
+		if !self.has_changed() {
+			return Ok(ObjectChanges::default())
+		}
+
+		self.generation = ObjectsImpl::generation(&self.objects.0);
+		let current: Vec<OwnedObject> = self.objects.snapshot()?.into();
+
+		let mut changes = ObjectChanges::default();
+		for object in current.iter() {
+			if !self.known.iter().any(|known| is_same_object(known, object)) {
+				changes.added.push(object.clone());
+			}
+		}
+		for known in self.known.iter() {
+			if !current.iter().any(|object| is_same_object(known, object)) {
+				changes.removed.push(known.clone());
+			}
+		}
+		self.known = current;
+
+		for object in changes.removed.iter() {
+			for f in self.on_removed.iter_mut() {
+				f(object);
+			}
+		}
+		for object in changes.added.iter() {
+			for f in self.on_added.iter_mut() {
+				f(object);
+			}
+		}
+		Ok(changes)
+	}
+
+	/// Blocks until an object matching `name` is loaded, or until `timeout` passes.
+	/// 
+	/// Returns the object immediately if it is already loaded,
+	/// after [polling](Self::poll) so that objects which were unloaded in the meantime aren't returned.
+	/// Names are matched in the same way as in [`Objects::map_by_name`].
+	pub fn wait_for(&mut self, name: &CStr, timeout: Duration) -> Result<Option<OwnedObject>, Error> {
+		let name = name.to_bytes();
+		let find = move |objects: &[OwnedObject]| {
+			objects.iter().find(|object| check_lib_name(object.name().to_bytes(), name)).cloned()
+		};
+
+		self.poll()?;
+		if let Some(object) = find(&self.known) {
+			return Ok(Some(object))
+		}
+
+		let deadline = Instant::now() + timeout;
+		loop {
+			let changes = self.poll()?;
+			if let Some(object) = find(&changes.added) {
+				return Ok(Some(object))
+			}
+
+			let now = Instant::now();
+			if now >= deadline {
+				return Ok(None)
+			}
+			sleep(self.poll_interval.min(deadline - now));
+		}
+	}
+}
+
+impl fmt::Debug for ObjectWatcher {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ObjectWatcher")
+			.field("known", &self.known)
+			.field("generation", &self.generation)
+			.field("poll_interval", &self.poll_interval)
+			.finish_non_exhaustive()
+	}
+}
+
+pub(crate) fn is_same_object(a: &OwnedObject, b: &OwnedObject) -> bool {
+	a.base_addr() == b.base_addr() && a.name() == b.name()
+}
+
+/// Objects that were loaded and unloaded between two polls of an [`ObjectWatcher`].
+#[derive(Debug, Clone, Default)]
+pub struct ObjectChanges {
+	pub added: Vec<OwnedObject>,
+	pub removed: Vec<OwnedObject>,
+}
+
+impl ObjectChanges {
+	/// Returns `true` if no objects were loaded or unloaded.
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty()
+	}
+}
@@ -0,0 +1,474 @@
+use ::core::fmt;
+
+/// Maximum length of an x86-64 instruction.
+pub const MAX_INSTRUCTION_LEN: usize = 15;
+
+/// Opcode map that an [`Instruction`]'s opcode byte belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpcodeMap {
+	/// One-byte opcodes.
+	Primary,
+	/// Opcodes prefixed by `0F`.
+	Secondary,
+	/// Opcodes prefixed by `0F 38`.
+	Map0F38,
+	/// Opcodes prefixed by `0F 3A`.
+	Map0F3A,
+}
+
+/// Decoded x86-64 instruction.
+/// 
+/// The decoder only finds the length of the instruction and the locations of its operands,
+/// which is enough to follow branches and resolve RIP-relative addresses.
+/// It supports the legacy, REX and VEX encodings, but not EVEX or 3DNow!.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Instruction {
+	addr: usize,
+	bytes: [u8; MAX_INSTRUCTION_LEN],
+	len: u8,
+	map: OpcodeMap,
+	opcode: u8,
+	rex: u8,
+	modrm: Option<u8>,
+	/// Offset and size of the displacement.
+	disp: Option<(u8, u8)>,
+	/// Offset and size of the immediate.
+	imm: Option<(u8, u8)>,
+	rip_relative: bool,
+	/// Whether the immediate is a branch offset relative to the next instruction.
+	relative: bool,
+}
+
+/// Sizes of the operands of an opcode.
+#[derive(Clone, Copy)]
+struct Operands {
+	modrm: bool,
+	imm: ImmSize,
+	relative: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ImmSize {
+	None,
+	Byte,
+	Word,
+	/// 16 bits with an operand size override, 32 bits otherwise.
+	Z,
+	/// 16 bits with an operand size override, 64 bits with `REX.W`, 32 bits otherwise.
+	V,
+	/// Address-sized memory offset.
+	Offset,
+	/// `ENTER`, which has a 16-bit and an 8-bit immediate.
+	Enter,
+}
+
+impl Operands {
+	const fn new(modrm: bool, imm: ImmSize) -> Self {
+		Self {
+			modrm,
+			imm,
+			relative: false,
+		}
+	}
+
+	const fn relative(imm: ImmSize) -> Self {
+		Self {
+			modrm: false,
+			imm,
+			relative: true,
+		}
+	}
+}
+
+/// Returns the operands of an opcode in the primary map,
+/// or `None` if it is invalid in 64-bit mode or is a prefix.
+const fn primary_operands(opcode: u8, modrm_reg: Option<u8>) -> Option<Operands> {
+	use ImmSize::*;
+	let operands = match opcode {
+		0x00..=0x3f => match opcode & 7 {
+			0..=3 => Operands::new(true, None),
+			4 => Operands::new(false, Byte),
+			5 => Operands::new(false, Z),
+			// Segment pushes and pops, BCD adjustments and prefixes.
+			_ => return Option::None,
+		},
+		0x50..=0x5f => Operands::new(false, None),
+		0x63 => Operands::new(true, None),
+		0x68 => Operands::new(false, Z),
+		0x69 => Operands::new(true, Z),
+		0x6a => Operands::new(false, Byte),
+		0x6b => Operands::new(true, Byte),
+		0x6c..=0x6f => Operands::new(false, None),
+		0x70..=0x7f => Operands::relative(Byte),
+		0x80 | 0x83 => Operands::new(true, Byte),
+		0x81 => Operands::new(true, Z),
+		0x84..=0x8f => Operands::new(true, None),
+		0x90..=0x99 | 0x9b..=0x9f => Operands::new(false, None),
+		0xa0..=0xa3 => Operands::new(false, Offset),
+		0xa4..=0xa7 | 0xaa..=0xaf => Operands::new(false, None),
+		0xa8 => Operands::new(false, Byte),
+		0xa9 => Operands::new(false, Z),
+		0xb0..=0xb7 => Operands::new(false, Byte),
+		0xb8..=0xbf => Operands::new(false, V),
+		0xc0 | 0xc1 | 0xc6 => Operands::new(true, Byte),
+		0xc7 => Operands::new(true, Z),
+		0xc2 | 0xca => Operands::new(false, Word),
+		0xc3 | 0xc9 | 0xcb | 0xcc | 0xcf => Operands::new(false, None),
+		0xc8 => Operands::new(false, Enter),
+		0xcd => Operands::new(false, Byte),
+		0xd0..=0xd3 | 0xd8..=0xdf => Operands::new(true, None),
+		0xe0..=0xe3 | 0xeb => Operands::relative(Byte),
+		0xe4..=0xe7 => Operands::new(false, Byte),
+		0xe8 | 0xe9 => Operands::relative(Z),
+		0xec..=0xef | 0xf1 | 0xf4 | 0xf5 | 0xf8..=0xfd => Operands::new(false, None),
+		// `TEST` has an immediate, while the other instructions of these groups don't.
+		0xf6 => match modrm_reg {
+			Some(0 | 1) => Operands::new(true, Byte),
+			_ => Operands::new(true, None),
+		},
+		0xf7 => match modrm_reg {
+			Some(0 | 1) => Operands::new(true, Z),
+			_ => Operands::new(true, None),
+		},
+		0xfe | 0xff => Operands::new(true, None),
+		_ => return Option::None,
+	};
+	Some(operands)
+}
+
+/// Returns the operands of an opcode in the secondary map,
+/// or `None` if it isn't supported.
+const fn secondary_operands(opcode: u8) -> Option<Operands> {
+	use ImmSize::*;
+	let operands = match opcode {
+		0x05..=0x09 | 0x0b | 0x0e | 0x30..=0x37 | 0x77 | 0xa0..=0xa2 | 0xa8..=0xaa | 0xc8..=0xcf => {
+			Operands::new(false, None)
+		}
+		0x0f | 0x38 | 0x3a | 0x04 | 0x0a | 0x0c | 0x24..=0x27 | 0x39 | 0x3b..=0x3f | 0x7a | 0x7b | 0xa6 | 0xa7 | 0xff => {
+			return Option::None
+		}
+		0x70..=0x73 | 0xa4 | 0xac | 0xba | 0xc2 | 0xc4..=0xc6 => Operands::new(true, Byte),
+		0x80..=0x8f => Operands::relative(Z),
+		_ => Operands::new(true, None),
+	};
+	Some(operands)
+}
+
+/// Returns `true` if `byte` is a legacy prefix.
+const fn is_legacy_prefix(byte: u8) -> bool {
+	matches!(byte, 0x26 | 0x2e | 0x36 | 0x3e | 0x64..=0x67 | 0xf0 | 0xf2 | 0xf3)
+}
+
+impl Instruction {
+	/// Decodes the instruction at the start of `bytes`, which is located at `addr`.
+	/// 
+	/// Returns `None` if the instruction is invalid, unsupported,
+	/// or longer than `bytes`.
+	pub fn decode(bytes: &[u8], addr: usize) -> Option<Self> {
+		let limit = bytes.len().min(MAX_INSTRUCTION_LEN);
+		let mut pos = 0;
+		let mut operand_size_override = false;
+		let mut address_size_override = false;
+		while pos < limit && is_legacy_prefix(bytes[pos]) {
+			match bytes[pos] {
+				0x66 => operand_size_override = true,
+				0x67 => address_size_override = true,
+				_ => {}
+			}
+			pos += 1;
+		}
+
+		let mut rex = 0;
+		if let Some(&byte @ 0x40..=0x4f) = bytes.get(pos) {
+			rex = byte;
+			pos += 1;
+		}
+
+		let first = *bytes.get(pos)?;
+		let (map, opcode, operands) = match first {
+			// VEX prefixes, which can't be combined with REX.
+			0xc4 | 0xc5 if rex == 0 => {
+				let (map, vex_len) = if first == 0xc5 {
+					(OpcodeMap::Secondary, 2)
+				} else {
+					let map = match *bytes.get(pos + 1)? & 0x1f {
+						1 => OpcodeMap::Secondary,
+						2 => OpcodeMap::Map0F38,
+						3 => OpcodeMap::Map0F3A,
+						_ => return None,
+					};
+					if *bytes.get(pos + 2)? & 0x80 != 0 {
+						rex = 0x48;
+					}
+					(map, 3)
+				};
+				pos += vex_len;
+				let opcode = *bytes.get(pos)?;
+				pos += 1;
+				let imm = match (map, opcode) {
+					(OpcodeMap::Map0F3A, _) | (OpcodeMap::Secondary, 0x70..=0x73 | 0xc2 | 0xc4..=0xc6) => ImmSize::Byte,
+					_ => ImmSize::None,
+				};
+				let modrm = !matches!((map, opcode), (OpcodeMap::Secondary, 0x77));
+				(map, opcode, Operands::new(modrm, imm))
+			}
+			0x0f => {
+				let second = *bytes.get(pos + 1)?;
+				match second {
+					0x38 => {
+						pos += 3;
+						(OpcodeMap::Map0F38, *bytes.get(pos - 1)?, Operands::new(true, ImmSize::None))
+					}
+					0x3a => {
+						pos += 3;
+						(OpcodeMap::Map0F3A, *bytes.get(pos - 1)?, Operands::new(true, ImmSize::Byte))
+					}
+					_ => {
+						pos += 2;
+						(OpcodeMap::Secondary, second, secondary_operands(second)?)
+					}
+				}
+			}
+			_ => {
+				pos += 1;
+				let modrm_reg = bytes.get(pos).map(move |&modrm| (modrm >> 3) & 7);
+				(OpcodeMap::Primary, first, primary_operands(first, modrm_reg)?)
+			}
+		};
+
+		let mut modrm = None;
+		let mut disp = None;
+		let mut rip_relative = false;
+		if operands.modrm {
+			let byte = *bytes.get(pos)?;
+			modrm = Some(byte);
+			pos += 1;
+			let mode = byte >> 6;
+			let rm = byte & 7;
+			if mode != 3 {
+				let mut disp_size = match mode {
+					1 => 1,
+					2 => 4,
+					_ => 0,
+				};
+				if rm == 4 {
+					let sib = *bytes.get(pos)?;
+					pos += 1;
+					if mode == 0 && sib & 7 == 5 {
+						disp_size = 4;
+					}
+				} else if mode == 0 && rm == 5 {
+					rip_relative = true;
+					disp_size = 4;
+				}
+				if disp_size != 0 {
+					disp = Some((pos as u8, disp_size));
+					pos += disp_size as usize;
+				}
+			}
+		}
+
+		let imm_size = match operands.imm {
+			ImmSize::None => 0,
+			ImmSize::Byte => 1,
+			ImmSize::Word => 2,
+			ImmSize::Enter => 3,
+			ImmSize::Z if operand_size_override => 2,
+			ImmSize::Z => 4,
+			ImmSize::V if rex & 0x08 != 0 => 8,
+			ImmSize::V if operand_size_override => 2,
+			ImmSize::V => 4,
+			ImmSize::Offset if address_size_override => 4,
+			ImmSize::Offset => 8,
+		};
+		let imm = (imm_size != 0).then_some((pos as u8, imm_size));
+		pos += imm_size as usize;
+
+		if pos > limit {
+			return None
+		}
+		let mut instruction_bytes = [0; MAX_INSTRUCTION_LEN];
+		instruction_bytes[..pos].copy_from_slice(&bytes[..pos]);
+		Some(Self {
+			addr,
+			bytes: instruction_bytes,
+			len: pos as u8,
+			map,
+			opcode,
+			rex,
+			modrm,
+			disp,
+			imm,
+			rip_relative,
+			relative: operands.relative,
+		})
+	}
+
+	/// Returns the address that the instruction was decoded at.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	pub const fn len(&self) -> usize {
+		self.len as usize
+	}
+
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns the encoded bytes of the instruction.
+	pub fn bytes(&self) -> &[u8] {
+		&self.bytes[..self.len()]
+	}
+
+	/// Returns the address of the instruction that follows this one.
+	pub const fn next_addr(&self) -> usize {
+		self.addr.wrapping_add(self.len())
+	}
+
+	pub const fn map(&self) -> OpcodeMap {
+		self.map
+	}
+
+	/// Returns the opcode byte of the instruction in its [`map`](Self::map).
+	pub const fn opcode(&self) -> u8 {
+		self.opcode
+	}
+
+	/// Returns the REX prefix of the instruction, or `0` if it doesn't have one.
+	/// 
+	/// For VEX-encoded instructions, only `REX.W` is reflected.
+	pub const fn rex(&self) -> u8 {
+		self.rex
+	}
+
+	pub const fn modrm(&self) -> Option<u8> {
+		self.modrm
+	}
+
+	/// Returns the `reg` field of the ModR/M byte, extended by `REX.R`.
+	/// 
+	/// This is usually the register operand of the instruction.
+	pub const fn modrm_reg(&self) -> Option<u8> {
+		match self.modrm {
+			Some(modrm) => Some(((modrm >> 3) & 7) | ((self.rex & 0x04) << 1)),
+			None => None,
+		}
+	}
+
+	/// Returns `true` if the memory operand of the instruction is relative to `RIP`.
+	pub const fn is_rip_relative(&self) -> bool {
+		self.rip_relative
+	}
+
+	fn read_signed(&self, (offset, size): (u8, u8)) -> i64 {
+		let offset = offset as usize;
+		let mut buffer = [0; 8];
+		buffer[..size as usize].copy_from_slice(&self.bytes[offset..offset + size as usize]);
+		let shift = 64 - 8 * size as u32;
+		(i64::from_le_bytes(buffer) << shift) >> shift
+	}
+
+	/// Returns the offset of the displacement in the instruction.
+	pub fn displacement_offset(&self) -> Option<usize> {
+		self.disp.map(move |(offset, _)| offset as usize)
+	}
+
+	/// Returns the sign-extended displacement of the memory operand,
+	/// such as the struct offset in `mov rax, [rcx+0x10]`.
+	pub fn displacement(&self) -> Option<i32> {
+		self.disp.map(move |disp| self.read_signed(disp) as i32)
+	}
+
+	/// Returns the offset of the immediate in the instruction.
+	pub fn immediate_offset(&self) -> Option<usize> {
+		self.imm.map(move |(offset, _)| offset as usize)
+	}
+
+	/// Returns the sign-extended immediate of the instruction.
+	/// 
+	/// For relative branches, this is the offset from the [next instruction](Self::next_addr).
+	pub fn immediate(&self) -> Option<i64> {
+		self.imm.map(move |imm| self.read_signed(imm))
+	}
+
+	/// Returns `true` if the instruction is a relative branch,
+	/// such as `call rel32`, `jmp rel8` or `jcc rel32`.
+	pub const fn is_relative_branch(&self) -> bool {
+		self.relative
+	}
+
+	/// Returns `true` if the instruction is a `call rel32`.
+	pub fn is_call(&self) -> bool {
+		self.map == OpcodeMap::Primary && self.opcode == 0xe8
+	}
+
+	/// Returns `true` if the instruction is an unconditional `jmp rel8` or `jmp rel32`.
+	pub fn is_jump(&self) -> bool {
+		self.map == OpcodeMap::Primary && matches!(self.opcode, 0xe9 | 0xeb)
+	}
+
+	/// Returns the absolute target of a relative branch.
+	pub fn branch_target(&self) -> Option<usize> {
+		if !self.relative {
+			return None
+		}
+		Some(self.next_addr().wrapping_add_signed(self.immediate()? as isize))
+	}
+
+	/// Returns the absolute address of a RIP-relative memory operand,
+	/// such as the one of `lea rax, [rip+disp32]`.
+	pub fn rip_target(&self) -> Option<usize> {
+		if !self.rip_relative {
+			return None
+		}
+		Some(self.next_addr().wrapping_add_signed(self.displacement()? as isize))
+	}
+
+	/// Returns either the [`branch_target`](Self::branch_target)
+	/// or the [`rip_target`](Self::rip_target) of the instruction.
+	pub fn target(&self) -> Option<usize> {
+		self.branch_target().or_else(move || self.rip_target())
+	}
+}
+
+impl fmt::Debug for Instruction {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Instruction")
+			.field("addr", &format_args!("{:#x}", self.addr))
+			.field("bytes", &format_args!("{:02x?}", self.bytes()))
+			.field("map", &self.map)
+			.field("opcode", &format_args!("{:#04x}", self.opcode))
+			.finish()
+	}
+}
+
+/// Iterator that decodes consecutive instructions in a slice.
+/// 
+/// Decoding stops at the first instruction that can't be decoded.
+#[derive(Debug, Clone)]
+pub struct Instructions<'a> {
+	bytes: &'a [u8],
+	addr: usize,
+}
+
+impl<'a> Instructions<'a> {
+	/// Creates an iterator over the instructions in `bytes`, which are located at `addr`.
+	pub const fn new(bytes: &'a [u8], addr: usize) -> Self {
+		Self {
+			bytes,
+			addr,
+		}
+	}
+}
+
+impl Iterator for Instructions<'_> {
+	type Item = Instruction;
+	fn next(&mut self) -> Option<Self::Item> {
+		let instruction = Instruction::decode(self.bytes, self.addr)?;
+		self.bytes = &self.bytes[instruction.len()..];
+		self.addr = instruction.next_addr();
+		Some(instruction)
+	}
+}
@@ -0,0 +1,170 @@
+use crate::{
+	scan::segment_bytes,
+	snapshot::OwnedObject,
+	Object, Segment,
+};
+#[cfg(target_arch = "x86_64")]
+use crate::{
+	scan::is_code_segment,
+	x86_64::{
+		Instruction, OpcodeMap,
+	},
+};
+
+/// Reference to an address from the code or data of an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Xref {
+	addr: usize,
+	segment: usize,
+	function: Option<usize>,
+}
+
+impl Xref {
+	pub(crate) const fn new(addr: usize, segment: usize, function: Option<usize>) -> Self {
+		Self {
+			addr,
+			segment,
+			function,
+		}
+	}
+
+	/// Returns the absolute address of the referencing instruction or value.
+	pub const fn addr(&self) -> usize {
+		self.addr
+	}
+
+	/// Returns the index of the segment that contains the reference in its object.
+	pub const fn segment_index(&self) -> usize {
+		self.segment
+	}
+
+	/// Returns the absolute address of the start of the function that contains the reference,
+	/// if the object has unwind information for it.
+	pub const fn function(&self) -> Option<usize> {
+		self.function
+	}
+}
+
+/// References to an address from one of the loaded objects,
+/// as returned by [`Objects::pointer_xrefs`](crate::Objects::pointer_xrefs).
+#[derive(Debug, Clone)]
+pub struct ObjectXrefs {
+	object: OwnedObject,
+	xrefs: Vec<Xref>,
+}
+
+impl ObjectXrefs {
+	pub(crate) const fn new(object: OwnedObject, xrefs: Vec<Xref>) -> Self {
+		Self {
+			object,
+			xrefs,
+		}
+	}
+
+	/// Returns the object that contains the references.
+	pub const fn object(&self) -> &OwnedObject {
+		&self.object
+	}
+
+	pub fn xrefs(&self) -> &[Xref] {
+		&self.xrefs
+	}
+
+	pub fn into_xrefs(self) -> Vec<Xref> {
+		self.xrefs
+	}
+}
+
+/// Finds the aligned pointer-sized values equal to `target` in the writable segments of `object`,
+/// which include the data that is made read-only after relocation (`PT_GNU_RELRO`).
+pub(crate) fn pointer_xrefs(object: &Object<'_>, target: usize) -> Vec<Xref> {
+	let mut xrefs = Vec::new();
+	let is_data = |segment: &Segment<'_>| segment.is_loadable() && segment.flags().has_w() && segment.size() != 0;
+	for (index, segment) in object.segments().enumerate().filter(move |(_, segment)| is_data(segment)) {
+		// SAFETY: Writable loadable segments are mapped and readable for as long as their object is loaded.
+		let data = unsafe { segment_bytes(&segment) };
+		let skip = segment.addr().wrapping_neg() % size_of::<usize>();
+		let Some(aligned) = data.get(skip..) else {
+			continue
+		};
+		let target = target.to_ne_bytes();
+		let found = aligned.chunks_exact(size_of::<usize>())
+			.enumerate()
+			.filter(move |(_, value)| *value == target)
+			.map(move |(i, _)| Xref::new(segment.addr() + skip + i * size_of::<usize>(), index, None));
+		xrefs.extend(found);
+	}
+	xrefs
+}
+
+/// Finds the instructions in the executable segments of `object`
+/// that reference `target` through a RIP-relative displacement at their very end,
+/// and for which `accept` returns `true`.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn rip_relative_xrefs<F>(object: &Object<'_>, target: usize, mut accept: F) -> Vec<Xref>
+where
+	F: FnMut(&Instruction) -> bool,
+{
+	let mut xrefs = Vec::new();
+	for (index, segment) in object.segments().enumerate().filter(move |(_, segment)| is_code_segment(segment)) {
+		// SAFETY: See `Scan::next`.
+		let data = unsafe { segment_bytes(&segment) };
+		for_each_rel32(&segment, data, target, |disp_offset| {
+			// The displacement is preceded by at least an opcode and a ModR/M byte,
+			// and possibly by prefixes.
+			let found = (2..=4)
+				.filter_map(move |len| disp_offset.checked_sub(len))
+				.filter_map(|start| Instruction::decode(&data[start..], segment.addr() + start))
+				.find(|instruction| {
+					instruction.displacement_offset().is_some_and(|offset| instruction.addr() + offset == segment.addr() + disp_offset)
+						&& instruction.len() == disp_offset + 4 - (instruction.addr() - segment.addr())
+						&& instruction.rip_target() == Some(target)
+						&& accept(instruction)
+				});
+			if let Some(instruction) = found {
+				xrefs.push(Xref::new(instruction.addr(), index, object.function_start(instruction.addr())));
+			}
+		});
+	}
+	xrefs
+}
+
+/// Calls `f` with the offset of every 32-bit value in `data`
+/// that is a displacement from its own end to `target`.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn for_each_rel32<F: FnMut(usize)>(segment: &Segment<'_>, data: &[u8], target: usize, mut f: F) {
+	let addr = segment.addr();
+	for (offset, window) in data.windows(4).enumerate() {
+		let disp = i32::from_le_bytes([window[0], window[1], window[2], window[3]]);
+		if addr.wrapping_add(offset + 4).wrapping_add_signed(disp as isize) == target {
+			f(offset);
+		}
+	}
+}
+
+/// Finds the `call rel32` and `jmp rel32` instructions in the executable segments of `object`
+/// that branch to `target`.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn call_xrefs(object: &Object<'_>, target: usize) -> Vec<Xref> {
+	let mut xrefs = Vec::new();
+	for (index, segment) in object.segments().enumerate().filter(move |(_, segment)| is_code_segment(segment)) {
+		// SAFETY: See `Scan::next`.
+		let data = unsafe { segment_bytes(&segment) };
+		for_each_rel32(&segment, data, target, |disp_offset| {
+			let Some(start) = disp_offset.checked_sub(1) else {
+				return
+			};
+			if matches!(data[start], 0xe8 | 0xe9) {
+				let addr = segment.addr() + start;
+				xrefs.push(Xref::new(addr, index, object.function_start(addr)));
+			}
+		});
+	}
+	xrefs
+}
+
+/// Returns `true` if `instruction` is a `lea` or `mov` that can reference data.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn is_lea_or_mov(instruction: &Instruction) -> bool {
+	instruction.map() == OpcodeMap::Primary && matches!(instruction.opcode(), 0x8d | 0x8b | 0x89)
+}